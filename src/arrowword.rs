@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::utils::{Board, Dir, WordPos, CLUE_CELL};
+
+/// Clues held by a single clue cell
+pub const MAX_CLUES_PER_CELL: usize = 2;
+
+/// Clue cell (x, y), arrow and word of a clue
+pub type ArrowSlot = (usize, usize, Arrow, WordPos);
+
+
+/// Arrow from a clue cell to the first letter of its word
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Arrow {
+    /// across word starting right of the clue cell
+    Right,
+    /// down word starting below the clue cell
+    Down,
+    /// across word starting below the clue cell
+    DownRight,
+    /// down word starting right of the clue cell
+    RightDown,
+}
+
+impl Arrow {
+    /// Arrows pointing to a word in the given direction, straight arrows first
+    pub fn for_dir(dir: Dir) -> [Arrow; 2] {
+        match dir {
+            Dir::HOR => [Arrow::Right, Arrow::DownRight],
+            Dir::VER => [Arrow::Down, Arrow::RightDown],
+        }
+    }
+
+    pub fn symbol(&self) -> char {
+        match self {
+            Arrow::Right => '→',
+            Arrow::Down => '↓',
+            Arrow::DownRight => '↳',
+            Arrow::RightDown => '↴',
+        }
+    }
+
+    // Offset of the first letter of the word from the clue cell
+    fn offset(&self) -> (usize, usize) {
+        match self {
+            Arrow::Right | Arrow::RightDown => (1, 0),
+            Arrow::Down | Arrow::DownRight => (0, 1),
+        }
+    }
+}
+
+
+/// Clue written in a clue cell of an arrowword
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ArrowClue {
+    pub x: usize,
+    pub y: usize,
+    pub arrow: Arrow,
+    pub pos: WordPos,
+    pub text: String,
+}
+
+
+/// Find the clue cell of each word of the board: the cell before the word in its direction
+/// or, if it is not a free clue cell, the cell beside its first letter (bent arrow).
+/// Each clue cell holds at most [`MAX_CLUES_PER_CELL`] clues.
+/// Returns the words left without a clue cell as error
+///
+/// ```
+/// use crosswords_generator::arrowword::{arrow_slots, Arrow};
+/// use crosswords_generator::Board;
+///
+/// let rows: Vec<String> = ["@@@", "@  ", "@  "].iter().map(|r| r.to_string()).collect();
+/// let slots = arrow_slots(&Board::from_rows(&rows)).unwrap();
+/// assert_eq!(slots.len(), 4);
+/// assert!(slots.iter().any(|&(x, y, arrow, _)| (x, y, arrow) == (0, 1, Arrow::Right)));
+///
+/// let rows: Vec<String> = ["@  ", "   "].iter().map(|r| r.to_string()).collect();
+/// assert!(arrow_slots(&Board::from_rows(&rows)).is_err());
+/// ```
+pub fn arrow_slots(board: &Board) -> Result<Vec<ArrowSlot>, Vec<WordPos>> {
+    let mut slots = Vec::new();
+    let mut clues_in_cell: HashMap<(usize, usize), usize> = HashMap::new();
+    let mut missing = board.get_words_pos();
+
+    // straight arrows first, bent arrows for the words left
+    for k in 0..2 {
+        missing.retain(|word_pos| {
+            let arrow = Arrow::for_dir(word_pos.dir)[k];
+            let (dx, dy) = arrow.offset();
+            let Some((x, y)) = word_pos.x.checked_sub(dx).zip(word_pos.y.checked_sub(dy)) else {
+                return true;
+            };
+            let count = clues_in_cell.entry((x, y)).or_default();
+            if board.get(x, y).ok() != Some(CLUE_CELL) || *count >= MAX_CLUES_PER_CELL {
+                return true;
+            }
+            *count += 1;
+            slots.push((x, y, arrow, *word_pos));
+            false
+        });
+    }
+
+    if missing.is_empty() {
+        slots.sort_by_key(|&(x, y, arrow, _)| (y, x, arrow as usize));
+        Ok(slots)
+    }
+    else {
+        Err(missing)
+    }
+}
+
+
+/// Place the definitions of the words in their clue cells, see [`arrow_slots`]
+pub fn arrow_clues(board: &Board, definitions: &[(WordPos, String)]) -> Result<Vec<ArrowClue>, Vec<WordPos>> {
+    Ok(arrow_slots(board)?.into_iter()
+        .map(|(x, y, arrow, pos)| ArrowClue {
+            x,
+            y,
+            arrow,
+            pos,
+            text: definitions.iter().find(|(wp, _)| *wp == pos).map(|(_, def)| def.clone()).unwrap_or_default(),
+        })
+        .collect())
+}
@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use serde::Serialize;
+
+use crate::dictionary::{Dictionary, WordIndex};
+use crate::error::Error;
+use crate::generator::{generate, get_definitions, GenerateOptions};
+use crate::project::Project;
+use crate::utils::Board;
+
+/// Number of seeds tried for each puzzle before giving up
+const MAX_ATTEMPTS: usize = 10;
+
+
+/// Settings of a batch run
+pub struct BatchSettings {
+    pub count: usize,
+    pub out_dir: String,
+    /// template used for every puzzle, otherwise a pattern is generated for each puzzle
+    pub template: Option<Project>,
+    pub width: usize,
+    pub height: usize,
+    pub black_density: f64,
+    pub symmetric: bool,
+    pub unique_answers: bool,
+    pub rep_words: bool,
+    pub seed: u64,
+    pub dictionary: String,
+    /// translations of a bilingual batch, saved in the projects
+    pub translations: Option<String>,
+    pub reverse_translations: bool,
+}
+
+
+/// Outcome of one puzzle of the batch
+#[derive(Debug, Serialize)]
+pub struct BatchEntry {
+    pub file: Option<String>,
+    pub seed: u64,
+    pub attempts: usize,
+    pub found: bool,
+    pub time_elapsed: u128,
+    pub visited_nodes: u64,
+    /// error of the last attempt that could not run, e.g. no words left for a length
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+
+/// Summary of a batch run, also written as report.json
+#[derive(Debug, Serialize)]
+pub struct BatchReport {
+    pub count: usize,
+    pub generated: usize,
+    pub time_elapsed: u128,
+    pub visited_nodes: u64,
+    pub puzzles: Vec<BatchEntry>,
+}
+
+
+/// Generate `count` distinct puzzles, saving each one as a project in the output directory
+/// together with a report.json summary
+pub fn run_batch(settings: &BatchSettings, dictionary: &Dictionary) -> Result<BatchReport, Error> {
+    let mut words_len: WordIndex = dictionary.index();
+    let export_error = |path: &Path, e: std::io::Error| Error::Export {
+        path: path.to_string_lossy().into_owned(),
+        reason: e.to_string(),
+    };
+    let out_dir = Path::new(&settings.out_dir);
+    fs::create_dir_all(out_dir).map_err(|e| export_error(out_dir, e))?;
+
+    let mut seen: HashSet<Vec<String>> = HashSet::new();
+    let mut puzzles: Vec<BatchEntry> = Vec::with_capacity(settings.count);
+    let mut seed = settings.seed;
+
+    for n in 0..settings.count {
+        let mut entry = BatchEntry {
+            file: None,
+            seed,
+            attempts: 0,
+            found: false,
+            time_elapsed: 0,
+            visited_nodes: 0,
+            error: None,
+        };
+
+        while entry.attempts < MAX_ATTEMPTS && !entry.found {
+            entry.attempts += 1;
+            entry.seed = seed;
+            seed = seed.wrapping_add(1);
+
+            // Create board
+            let mut board = match &settings.template {
+                Some(template) => template.board()?,
+                None => Board::random_pattern(settings.width, settings.height, settings.black_density,
+                    settings.symmetric, &mut StdRng::seed_from_u64(entry.seed)),
+            };
+            let template = board.clone();
+
+            // Find solution, the same index is reused for every puzzle
+            let options = GenerateOptions::new().shuffle(true).seed(entry.seed).repeat_words(settings.rep_words);
+            // A failed attempt is recorded like a board without solution and the next seed is tried
+            let sol = match generate(&mut board, &words_len, &options) {
+                Ok(sol) => sol,
+                Err(e) => {
+                    entry.error = Some(e.to_string());
+                    continue;
+                }
+            };
+            entry.error = None;
+            entry.time_elapsed += sol.time_elapsed;
+            entry.visited_nodes += sol.visited_nodes;
+
+            // Skip fills already in the batch
+            if !sol.found || !seen.insert(board.rows()) {
+                continue;
+            }
+            entry.found = true;
+
+            // Save project
+            let mut project = settings.template.clone()
+                .unwrap_or_else(|| Project::new(board.width(), board.height()));
+            if settings.template.is_none() {
+                project.template = template.rows();
+            }
+            project.set_solution(&board, &get_definitions(&board, dictionary)?);
+            project.seed = entry.seed;
+            project.dictionary = settings.dictionary.clone();
+            project.translations = settings.translations.clone();
+            project.reverse_translations = settings.reverse_translations;
+            project.shuffle = true;
+            project.repeat_words = settings.rep_words;
+
+            let file = format!("puzzle_{:03}.json", n + 1);
+            project.save(&out_dir.join(&file).to_string_lossy())?;
+            entry.file = Some(file);
+
+            // Remove answers from the index so they are not used again
+            if settings.unique_answers {
+                let answers: HashSet<String> = board.get_words_pos().iter()
+                    .map(|word_pos| board.get_word(word_pos))
+                    .collect();
+                for words in words_len.values_mut() {
+                    words.retain(|word| !answers.contains(*word));
+                }
+            }
+        }
+
+        puzzles.push(entry);
+    }
+
+    let report = BatchReport {
+        count: settings.count,
+        generated: puzzles.iter().filter(|p| p.found).count(),
+        time_elapsed: puzzles.iter().map(|p| p.time_elapsed).sum(),
+        visited_nodes: puzzles.iter().map(|p| p.visited_nodes).sum(),
+        puzzles,
+    };
+    let report_path = out_dir.join("report.json");
+    let content = serde_json::to_string_pretty(&report).map_err(|e| Error::Export {
+        path: report_path.to_string_lossy().into_owned(),
+        reason: e.to_string(),
+    })?;
+    fs::write(&report_path, content).map_err(|e| export_error(&report_path, e))?;
+
+    Ok(report)
+}
+
+
+/// Table of the puzzles of the batch, with the error of the puzzles that could not be generated
+impl fmt::Display for BatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "BATCH REPORT")?;
+        writeln!(f, "{:<18} {:>20} {:>8} {:>10} {:>14}", "puzzle", "seed", "attempts", "time (ms)", "visited nodes")?;
+        for puzzle in &self.puzzles {
+            writeln!(f, "{:<18} {:>20} {:>8} {:>10} {:>14}",
+                puzzle.file.as_deref().unwrap_or("not found"), puzzle.seed, puzzle.attempts,
+                puzzle.time_elapsed, puzzle.visited_nodes)?;
+            if let Some(error) = &puzzle.error {
+                writeln!(f, "    {}", error)?;
+            }
+        }
+        write!(f, "\nGenerated {} of {} puzzles in {} ms ({} visited nodes)",
+            self.generated, self.count, self.time_elapsed, self.visited_nodes)
+    }
+}
@@ -0,0 +1,311 @@
+use std::collections::{BTreeMap, HashMap};
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+use crate::dictionary::WordIndex;
+use crate::utils::Board;
+
+
+/// Codeword puzzle: each letter of the board is replaced by a number, some letters are given
+#[derive(Debug, Clone)]
+pub struct Codeword {
+    /// number of each letter, from 1
+    pub cipher: BTreeMap<char, usize>,
+    /// letters given to the solver
+    pub revealed: Vec<char>,
+    /// the revealed letters lead to a single solution with the dictionary
+    pub unique: bool,
+}
+
+
+impl Codeword {
+    /// Number of the letter of a cell, None for blocks and empty cells
+    pub fn number(&self, board: &Board, x: usize, y: usize) -> Option<usize> {
+        board.get(x, y).ok().and_then(|c| self.cipher.get(&c).copied())
+    }
+
+    /// Letter key of the puzzle: each number with its letter, if revealed
+    pub fn key(&self) -> Vec<(usize, Option<char>)> {
+        let mut key: Vec<(usize, Option<char>)> = self.cipher.iter()
+            .map(|(&c, &n)| (n, self.revealed.contains(&c).then_some(c)))
+            .collect();
+        key.sort();
+        key
+    }
+
+    /// Get the numbered grid as text with the revealed letters, followed by the letter key
+    pub fn pretty(&self, board: &Board) -> String {
+        let mut pretty = String::new();
+        for y in 0..board.height() {
+            for x in 0..board.width() {
+                let cell = match self.number(board, x, y) {
+                    Some(n) => match board.get(x, y) {
+                        Ok(c) if self.revealed.contains(&c) => format!("{:>2}{}", n, c),
+                        _ => format!("{:>2} ", n),
+                    },
+                    None => " ##".to_owned(),
+                };
+                pretty.push_str(&cell);
+                pretty.push(' ');
+            }
+            pretty.push('\n');
+        }
+        pretty.push('\n');
+        for (n, c) in self.key() {
+            pretty.push_str(&format!("{:>2}:{} ", n, c.unwrap_or('_')));
+        }
+        pretty
+    }
+}
+
+
+// Slots of the board as sequences of numbers, with the words that can fill each one
+struct Puzzle<'a> {
+    slots: Vec<Vec<usize>>,
+    // words of the length of the slot repeating letters where the slot repeats numbers
+    candidates: Vec<Vec<&'a str>>,
+    // slots of each number
+    number_slots: HashMap<usize, Vec<usize>>,
+}
+
+
+impl<'a> Puzzle<'a> {
+    fn new(slots: Vec<Vec<usize>>, words_len: &WordIndex<'a>) -> Puzzle<'a> {
+        // same number same letter, different numbers different letters
+        let same_pattern = |slot: &[usize], word: &str| {
+            let chars: Vec<char> = word.chars().collect();
+            (0..slot.len()).all(|i| (i + 1..slot.len()).all(|j| (slot[i] == slot[j]) == (chars[i] == chars[j])))
+        };
+        let candidates = slots.iter()
+            .map(|slot| words_len.get(&slot.len()).into_iter().flatten()
+                .filter(|word| same_pattern(slot, word))
+                .copied()
+                .collect())
+            .collect();
+
+        let mut number_slots: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (i, slot) in slots.iter().enumerate() {
+            for &n in slot {
+                let list = number_slots.entry(n).or_default();
+                if !list.contains(&i) {
+                    list.push(i);
+                }
+            }
+        }
+        Puzzle { slots, candidates, number_slots }
+    }
+
+    // Words of the list that fit the slot with the letters assigned so far,
+    // a letter given to a number cannot be used by the other numbers
+    fn narrow(slot: &[usize], list: &[&'a str], letters: &HashMap<usize, char>, used: &HashMap<char, usize>) -> Vec<&'a str> {
+        list.iter()
+            .filter(|word| slot.iter().zip(word.chars()).all(|(n, c)| match letters.get(n) {
+                Some(&l) => l == c,
+                None => !used.contains_key(&c),
+            }))
+            .copied()
+            .collect()
+    }
+
+    // Backtracking on the slots with unassigned numbers, most constrained slot first.
+    // The candidates of the slots are narrowed when their numbers get a letter
+    fn search(&self, candidates: &[&[&'a str]], letters: &mut HashMap<usize, char>, used: &mut HashMap<char, usize>,
+              limit: usize, solutions: &mut Vec<HashMap<usize, char>>) {
+        if solutions.len() >= limit {
+            return;
+        }
+
+        let mut best: Option<usize> = None;
+        for (i, slot) in self.slots.iter().enumerate() {
+            if candidates[i].is_empty() {
+                return;
+            }
+            if slot.iter().all(|n| letters.contains_key(n)) {
+                continue;
+            }
+            if best.is_none_or(|b| candidates[i].len() < candidates[b].len()) {
+                best = Some(i);
+            }
+        }
+        let Some(i) = best else {
+            solutions.push(letters.clone());
+            return;
+        };
+
+        for &word in candidates[i] {
+            // new letters of the word, not used by other numbers
+            let mut added: Vec<(usize, char)> = Vec::new();
+            let fits = self.slots[i].iter().zip(word.chars()).all(|(&n, c)| {
+                if !letters.contains_key(&n) && !added.contains(&(n, c)) {
+                    if used.contains_key(&c) {
+                        return false;
+                    }
+                    added.push((n, c));
+                }
+                true
+            });
+            if !fits {
+                continue;
+            }
+            for &(n, c) in &added {
+                letters.insert(n, c);
+                used.insert(c, n);
+            }
+
+            let mut touched: Vec<usize> = added.iter().flat_map(|(n, _)| self.number_slots[n].iter().copied()).collect();
+            touched.sort_unstable();
+            touched.dedup();
+            let narrowed: Vec<(usize, Vec<&str>)> = touched.into_iter()
+                .map(|k| (k, Puzzle::narrow(&self.slots[k], candidates[k], letters, used)))
+                .collect();
+            let mut next = candidates.to_vec();
+            for (k, list) in &narrowed {
+                next[*k] = list;
+            }
+            self.search(&next, letters, used, limit, solutions);
+
+            for (n, c) in added {
+                letters.remove(&n);
+                used.remove(&c);
+            }
+            if solutions.len() >= limit {
+                break;
+            }
+        }
+    }
+
+    // Up to `limit` assignments of letters to the numbers with the given letters fixed
+    fn solve(&self, fixed: &HashMap<usize, char>, limit: usize) -> Vec<HashMap<usize, char>> {
+        let mut letters = fixed.clone();
+        let mut used: HashMap<char, usize> = fixed.iter().map(|(&n, &c)| (c, n)).collect();
+        let lists: Vec<Vec<&str>> = self.slots.iter().zip(&self.candidates)
+            .map(|(slot, list)| Puzzle::narrow(slot, list, &letters, &used))
+            .collect();
+        let candidates: Vec<&[&str]> = lists.iter().map(Vec::as_slice).collect();
+        let mut solutions = Vec::new();
+        self.search(&candidates, &mut letters, &mut used, limit, &mut solutions);
+        solutions
+    }
+}
+
+
+/// Make a codeword puzzle from a filled board: the letters get shuffled numbers and the
+/// letters revealed make the solution the only one with the dictionary.
+/// Letters are revealed where another solution differs, then each one is dropped if the
+/// solution stays unique without it: the set is minimal (irreducible), not always the smallest one
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use crosswords_generator::codeword::codeword;
+/// use crosswords_generator::{Board, Dictionary};
+///
+/// let mut words = BTreeMap::new();
+/// for word in ["AB", "CD", "AC", "BD"] {
+///     words.insert(word.to_owned(), vec![]);
+/// }
+/// let dictionary = Dictionary::new(words);
+///
+/// // the grid can be transposed, a letter tells which way is right
+/// let board = Board::from_rows(&["AB".to_owned(), "CD".to_owned()]);
+/// let puzzle = codeword(&board, &dictionary.index(), 1);
+/// assert_eq!(puzzle.cipher.len(), 4);
+/// assert!(puzzle.unique);
+/// assert_eq!(puzzle.revealed.len(), 1);
+/// ```
+pub fn codeword(solution: &Board, words_len: &WordIndex, seed: u64) -> Codeword {
+    // letters of the board by decreasing frequency
+    let mut counts: BTreeMap<char, usize> = BTreeMap::new();
+    for y in 0..solution.height() {
+        for x in 0..solution.width() {
+            if solution.valid(x, y) {
+                *counts.entry(solution.get(x, y).unwrap_or(' ')).or_default() += 1;
+            }
+        }
+    }
+    counts.remove(&' ');
+    let mut by_frequency: Vec<char> = counts.keys().copied().collect();
+    by_frequency.sort_by_key(|c| std::cmp::Reverse(counts[c]));
+
+    let mut numbers: Vec<usize> = (1..=counts.len()).collect();
+    numbers.shuffle(&mut StdRng::seed_from_u64(seed));
+    let cipher: BTreeMap<char, usize> = counts.keys().copied().zip(numbers).collect();
+    let truth: HashMap<usize, char> = cipher.iter().map(|(&c, &n)| (n, c)).collect();
+
+    let slots = solution.get_words_pos().iter()
+        .map(|wp| solution.get_word(wp).chars().map(|c| cipher.get(&c).copied().unwrap_or(0)).collect())
+        .collect();
+    let puzzle = Puzzle::new(slots, words_len);
+    let fixed = |revealed: &[char]| -> HashMap<usize, char> {
+        revealed.iter().map(|c| (cipher[c], *c)).collect()
+    };
+    let is_unique = |revealed: &[char]| puzzle.solve(&fixed(revealed), 2).iter().all(|s| *s == truth);
+
+    // reveal the most frequent letter that another solution gets wrong
+    let mut revealed: Vec<char> = Vec::new();
+    let unique = loop {
+        let solutions = puzzle.solve(&fixed(&revealed), 2);
+        let Some(other) = solutions.iter().find(|s| **s != truth) else {
+            break !solutions.is_empty();
+        };
+        let Some(&c) = by_frequency.iter().find(|c| other.get(&cipher[*c]) != Some(*c)) else {
+            break false;
+        };
+        revealed.push(c);
+    };
+
+    // drop the letters not needed anymore
+    if unique {
+        for c in revealed.clone().into_iter().rev() {
+            let without: Vec<char> = revealed.iter().copied().filter(|&r| r != c).collect();
+            if is_unique(&without) {
+                revealed = without;
+            }
+        }
+    }
+
+    Codeword { cipher, revealed, unique }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dictionary::Dictionary;
+
+    fn dictionary(words: &[&str]) -> Dictionary {
+        Dictionary::new(words.iter().map(|w| (w.to_string(), vec![])).collect())
+    }
+
+    #[test]
+    fn no_unique_solution() {
+        // the answer CD is not in the dictionary
+        let dictionary = dictionary(&["AB", "AC", "BD"]);
+        let board = Board::from_rows(&["AB".to_owned(), "CD".to_owned()]);
+        let puzzle = codeword(&board, &dictionary.index(), 1);
+        assert!(!puzzle.unique);
+    }
+
+    #[test]
+    fn irreducible_letters() {
+        // the same grid with D E F G
+        let dictionary = dictionary(&["CAT", "COT", "TAO", "TOO", "DEF", "DGF", "FEG", "FGG", "TAT", "OAT"]);
+        let board = Board::from_rows(&["CAT".to_owned(), "O#A".to_owned(), "TOO".to_owned()]);
+        let words_len = dictionary.index();
+        let puzzle = codeword(&board, &words_len, 3);
+        assert!(puzzle.unique);
+        assert!(!puzzle.revealed.is_empty());
+
+        // each revealed letter is needed
+        let truth: HashMap<usize, char> = puzzle.cipher.iter().map(|(&c, &n)| (n, c)).collect();
+        let slots = board.get_words_pos().iter()
+            .map(|wp| board.get_word(wp).chars().map(|c| puzzle.cipher[&c]).collect())
+            .collect();
+        let solver = Puzzle::new(slots, &words_len);
+        for c in &puzzle.revealed {
+            let fixed: HashMap<usize, char> = puzzle.revealed.iter().filter(|r| *r != c).map(|r| (puzzle.cipher[r], *r)).collect();
+            assert!(solver.solve(&fixed, 2).iter().any(|s| *s != truth));
+        }
+    }
+}
@@ -0,0 +1,363 @@
+use std::cmp::Reverse;
+use std::fs;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+use crate::error::Error;
+use crate::utils::{Board, Dir};
+
+/// Longest word list read by [`load_words`]. Each attempt of [`criss_cross`] tries every word
+/// on every letter of the grid, so the time grows with the square of the words: 50 words
+/// with the default attempts take about a second
+pub const MAX_WORDS: usize = 50;
+
+
+/// Options of [`criss_cross`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CrissCrossOptions {
+    /// Orders of the words tried, the best layout is kept
+    pub attempts: usize,
+    /// Seed of the shuffled orders
+    pub seed: u64,
+}
+
+
+impl Default for CrissCrossOptions {
+    fn default() -> CrissCrossOptions {
+        CrissCrossOptions {
+            attempts: 200,
+            seed: 0,
+        }
+    }
+}
+
+
+impl CrissCrossOptions {
+    pub fn new() -> CrissCrossOptions {
+        CrissCrossOptions::default()
+    }
+
+    pub fn attempts(mut self, attempts: usize) -> CrissCrossOptions {
+        self.attempts = attempts;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> CrissCrossOptions {
+        self.seed = seed;
+        self
+    }
+}
+
+
+/// Layout found by [`criss_cross`]: the board has '#' in the cells without letters
+#[derive(Debug, Clone)]
+pub struct CrissCross {
+    pub board: Board,
+    /// words that do not cross the others in any position, shorter than 2 letters or repeated
+    pub unplaced: Vec<String>,
+    /// cells shared by an across and a down word
+    pub intersections: usize,
+}
+
+
+// Board growing in every direction while the words are placed, '#' in the cells without letters
+struct Layout {
+    board: Board,
+    // layout coordinates of the top left cell of the board, the first word starts at (0, 0)
+    origin: (i32, i32),
+    intersections: usize,
+}
+
+
+impl Default for Layout {
+    fn default() -> Layout {
+        Layout {
+            board: Board::new(0, 0),
+            origin: (0, 0),
+            intersections: 0,
+        }
+    }
+}
+
+
+impl Layout {
+    fn step(dir: Dir) -> (i32, i32) {
+        match dir {
+            Dir::HOR => (1, 0),
+            Dir::VER => (0, 1),
+        }
+    }
+
+    fn area(&self) -> i64 {
+        (self.board.width() * self.board.height()) as i64
+    }
+
+    // Bounds (x0, y0, x1, y1) of the board grown to hold the word
+    fn bounds_with(&self, len: usize, x: i32, y: i32, dir: Dir) -> (i32, i32, i32, i32) {
+        let (dx, dy) = Layout::step(dir);
+        let len = len as i32;
+        let (ox, oy) = self.origin;
+        if self.board.width() == 0 {
+            return (x, y, x + dx * (len - 1), y + dy * (len - 1));
+        }
+        let (x1, y1) = (ox + self.board.width() as i32 - 1, oy + self.board.height() as i32 - 1);
+        (ox.min(x), oy.min(y), x1.max(x + dx * (len - 1)), y1.max(y + dy * (len - 1)))
+    }
+
+    fn area_with(&self, word: &[char], x: i32, y: i32, dir: Dir) -> i64 {
+        let (x0, y0, x1, y1) = self.bounds_with(word.len(), x, y, dir);
+        (x1 - x0 + 1) as i64 * (y1 - y0 + 1) as i64
+    }
+
+    // Letter of the cell at layout coordinates (x, y), None for empty cells and outside the board
+    fn letter(&self, x: i32, y: i32) -> Option<char> {
+        let (i, j) = (x - self.origin.0, y - self.origin.1);
+        if i < 0 || j < 0 {
+            return None;
+        }
+        self.board.get(i as usize, j as usize).ok().filter(|&c| c != '#')
+    }
+
+    // A cell with a letter is in a word of the direction when it has a letter next to it in that
+    // direction: the words only touch where they cross
+    fn in_word(&self, x: i32, y: i32, dir: Dir) -> bool {
+        let (dx, dy) = Layout::step(dir);
+        self.letter(x - dx, y - dy).is_some() || self.letter(x + dx, y + dy).is_some()
+    }
+
+    // Number of crossings of the word placed at (x, y), None if it does not fit:
+    // letters must match, the word cannot touch other words except where it crosses them
+    fn crossings(&self, word: &[char], x: i32, y: i32, dir: Dir) -> Option<usize> {
+        let (dx, dy) = Layout::step(dir);
+        let len = word.len() as i32;
+        if self.letter(x - dx, y - dy).is_some() || self.letter(x + dx * len, y + dy * len).is_some() {
+            return None;
+        }
+
+        let mut crossings = 0;
+        for (k, &c) in word.iter().enumerate() {
+            let cell = (x + dx * k as i32, y + dy * k as i32);
+            match self.letter(cell.0, cell.1) {
+                Some(other) => {
+                    if other != c || self.in_word(cell.0, cell.1, dir) {
+                        return None;
+                    }
+                    crossings += 1;
+                },
+                None => {
+                    // no side neighbours, they would form new words
+                    if self.letter(cell.0 + dy, cell.1 + dx).is_some() || self.letter(cell.0 - dy, cell.1 - dx).is_some() {
+                        return None;
+                    }
+                },
+            }
+        }
+        Some(crossings)
+    }
+
+    // Grow the board to the given bounds, the new cells are empty
+    fn grow(&mut self, (x0, y0, x1, y1): (i32, i32, i32, i32)) {
+        let (width, height) = ((x1 - x0 + 1) as usize, (y1 - y0 + 1) as usize);
+        if (x0, y0) == self.origin && (width, height) == (self.board.width(), self.board.height()) {
+            return;
+        }
+        let mut board = Board::from_rows(&vec!["#".repeat(width); height]);
+        let (ox, oy) = ((self.origin.0 - x0) as usize, (self.origin.1 - y0) as usize);
+        for j in 0..self.board.height() {
+            for i in 0..self.board.width() {
+                let _ = board.set(ox + i, oy + j, self.board.get(i, j).unwrap_or('#'));
+            }
+        }
+        self.board = board;
+        self.origin = (x0, y0);
+    }
+
+    fn place(&mut self, word: &[char], x: i32, y: i32, dir: Dir) {
+        self.grow(self.bounds_with(word.len(), x, y, dir));
+        let (dx, dy) = Layout::step(dir);
+        for (k, &c) in word.iter().enumerate() {
+            let cell = (x + dx * k as i32, y + dy * k as i32);
+            if self.letter(cell.0, cell.1).is_some() {
+                self.intersections += 1;
+            }
+            let _ = self.board.set((cell.0 - self.origin.0) as usize, (cell.1 - self.origin.1) as usize, c);
+        }
+    }
+
+    // Best position of the word crossing the placed words: most crossings, then smallest area
+    fn best_position(&self, word: &[char]) -> Option<(i32, i32, Dir)> {
+        let mut best: Option<(usize, i64, (i32, i32, Dir))> = None;
+        for j in 0..self.board.height() {
+            for i in 0..self.board.width() {
+                let (cx, cy) = (self.origin.0 + i as i32, self.origin.1 + j as i32);
+                let Some(c) = self.letter(cx, cy) else {
+                    continue;
+                };
+                for (k, _) in word.iter().enumerate().filter(|&(_, &w)| w == c) {
+                    for dir in [Dir::HOR, Dir::VER] {
+                        let (dx, dy) = Layout::step(dir);
+                        let (x, y) = (cx - dx * k as i32, cy - dy * k as i32);
+                        let Some(crossings) = self.crossings(word, x, y, dir) else {
+                            continue;
+                        };
+                        let area = self.area_with(word, x, y, dir);
+                        if best.is_none_or(|(bc, ba, _)| crossings > bc || (crossings == bc && area < ba)) {
+                            best = Some((crossings, area, (x, y, dir)));
+                        }
+                    }
+                }
+            }
+        }
+        best.map(|(_, _, position)| position)
+    }
+}
+
+
+// Place the words in the given order, the words that do not fit are tried again
+// after the others until no more words can be placed
+fn layout(words: &[Vec<char>]) -> (Layout, Vec<usize>) {
+    let mut layout = Layout::default();
+    let mut left: Vec<usize> = (0..words.len()).collect();
+    if left.is_empty() {
+        return (layout, left);
+    }
+    layout.place(&words[left.remove(0)], 0, 0, Dir::HOR);
+
+    loop {
+        let before = left.len();
+        left.retain(|&i| match layout.best_position(&words[i]) {
+            Some((x, y, dir)) => {
+                layout.place(&words[i], x, y, dir);
+                false
+            },
+            None => true,
+        });
+        if left.is_empty() || left.len() == before {
+            return (layout, left);
+        }
+    }
+}
+
+
+/// Read the words to lay out from a text file, one word per line. Empty lines are skipped
+/// and the words are trimmed. The list must have at most [`MAX_WORDS`] words
+pub fn load_words(path: &str) -> Result<Vec<String>, Error> {
+    let list_error = |reason: String| Error::Dictionary { path: path.to_owned(), reason };
+    let words: Vec<String> = fs::read_to_string(path)
+        .map_err(|e| list_error(e.to_string()))?
+        .lines()
+        .map(|line| line.trim().to_owned())
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    if words.is_empty() {
+        return Err(list_error("the word list is empty".to_owned()));
+    }
+    if words.len() > MAX_WORDS {
+        return Err(list_error(format!("the word list has {} words, the maximum is {}", words.len(), MAX_WORDS)));
+    }
+    Ok(words)
+}
+
+
+/// Lay out the words in a free-form interlocking grid: each word crosses another one
+/// on a shared letter. Several orders of the words are tried, keeping the layout with
+/// the most placed words, then the most intersections and then the smallest area.
+/// The words are placed as they are written, words shorter than 2 letters are not placed
+/// and the copies of a repeated word are listed in `unplaced`
+///
+/// ```
+/// use crosswords_generator::crisscross::{criss_cross, CrissCrossOptions};
+///
+/// let layout = criss_cross(&["ALPHA", "PLANE", "HELLO", "ECHO"], &CrissCrossOptions::new().seed(3));
+/// assert!(layout.unplaced.is_empty());
+/// assert!(layout.intersections >= 3);
+/// assert_eq!(layout.board.get_words_pos().len(), 4);
+/// ```
+pub fn criss_cross(words: &[&str], options: &CrissCrossOptions) -> CrissCross {
+    let mut list: Vec<Vec<char>> = Vec::new();
+    let mut unplaced: Vec<String> = Vec::new();
+    for word in words {
+        let chars: Vec<char> = word.trim().chars().collect();
+        if chars.len() < 2 || list.contains(&chars) {
+            unplaced.push(word.to_string());
+        }
+        else {
+            list.push(chars);
+        }
+    }
+    // longest words first in the first attempt, they give more letters to cross
+    list.sort_by_key(|w| Reverse(w.len()));
+
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let mut order = list.clone();
+    let mut best: Option<(Layout, Vec<usize>, Vec<Vec<char>>)> = None;
+    for attempt in 0..options.attempts.max(1) {
+        if attempt > 0 {
+            order.shuffle(&mut rng);
+        }
+        let (layout, left) = layout(&order);
+        let score = |layout: &Layout, left: &[usize]| (Reverse(left.len()), layout.intersections, Reverse(layout.area()));
+        let better = best.as_ref().is_none_or(|(b, b_left, _)| score(&layout, &left) > score(b, b_left));
+        if better {
+            best = Some((layout, left, order.clone()));
+        }
+    }
+
+    let (layout, left, order) = best.unwrap_or_default();
+    unplaced.extend(left.iter().map(|&i| order[i].iter().collect::<String>()));
+    CrissCross {
+        board: layout.board,
+        unplaced,
+        intersections: layout.intersections,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placed(layout: &CrissCross) -> usize {
+        layout.board.get_words_pos().len()
+    }
+
+    #[test]
+    fn repeated_words() {
+        let layout = criss_cross(&["HELLO", "WORLD", "HELLO", "LOW"], &CrissCrossOptions::new());
+        assert_eq!(layout.unplaced, ["HELLO"]);
+        assert_eq!(placed(&layout), 3);
+    }
+
+    #[test]
+    fn words_that_do_not_fit() {
+        // no shared letters, and a single letter
+        let layout = criss_cross(&["ABC", "XYZ", "CAB", "Q"], &CrissCrossOptions::new());
+        assert_eq!(layout.unplaced, ["Q", "XYZ"]);
+        assert_eq!(placed(&layout), 2);
+        assert_eq!(layout.intersections, 1);
+
+        let layout = criss_cross(&[], &CrissCrossOptions::new());
+        assert_eq!((layout.board.width(), layout.board.height()), (0, 0));
+    }
+
+    #[test]
+    fn longest_list() {
+        let letters = ['A', 'E', 'I', 'O', 'R', 'S', 'T', 'N'];
+        let words: Vec<String> = (0..MAX_WORDS)
+            .map(|i| (0..5 + i % 3).map(|k| letters[(i * 7 + k * (i % 5 + 1)) % letters.len()]).collect())
+            .collect();
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+
+        let layout = criss_cross(&words, &CrissCrossOptions::new().attempts(10));
+        assert_eq!(placed(&layout) + layout.unplaced.len(), MAX_WORDS);
+        assert!(placed(&layout) > MAX_WORDS / 2);
+
+        let path = std::env::temp_dir().join("crisscross_longest_list.txt");
+        fs::write(&path, [words.join("\n"), "EXTRA".to_owned()].join("\n")).unwrap();
+        assert!(load_words(&path.to_string_lossy()).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+
+use crate::error::Error;
+
+/// Map from word length to the words of that length, used by the solver
+pub type WordIndex<'a> = HashMap<usize, Vec<&'a str>>;
+
+
+/// Words with their definitions, ordered alphabetically
+#[derive(Debug, Clone, Default)]
+pub struct Dictionary {
+    words: BTreeMap<String, Vec<String>>,
+}
+
+
+impl Dictionary {
+    pub fn new(words: BTreeMap<String, Vec<String>>) -> Dictionary {
+        Dictionary { words }
+    }
+
+    /// Read a dictionary from a json file: an object mapping each word to the list of its definitions
+    pub fn load(path: &str) -> Result<Dictionary, Error> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| Error::Dictionary { path: path.to_owned(), reason: e.to_string() })?;
+        Dictionary::from_json(&content)
+            .map_err(|reason| Error::Dictionary { path: path.to_owned(), reason })
+    }
+
+    /// Parse a dictionary from its json content
+    pub fn from_json(content: &str) -> Result<Dictionary, String> {
+        let words: BTreeMap<String, Vec<String>> = serde_json::from_str(content)
+            .map_err(|e| format!("JSON was not well-formatted: {}", e))?;
+        Ok(Dictionary::new(words))
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains_key(word)
+    }
+
+    /// Get the definitions of a word
+    pub fn definitions(&self, word: &str) -> Option<&[String]> {
+        self.words.get(word).map(Vec::as_slice)
+    }
+
+    /// Iterate over the words in alphabetical order
+    pub fn words(&self) -> impl Iterator<Item = &str> {
+        self.words.keys().map(String::as_str)
+    }
+
+    /// Bilingual dictionary: the words having a translation, with their translations as definitions,
+    /// so that the clues are in the other language and the solver only uses translated words
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use crosswords_generator::dictionary::Translations;
+    /// use crosswords_generator::Dictionary;
+    ///
+    /// let mut words = BTreeMap::new();
+    /// words.insert("CASA".to_owned(), vec!["Abitazione".to_owned()]);
+    /// words.insert("CANE".to_owned(), vec!["Animale domestico".to_owned()]);
+    /// let mut translations = BTreeMap::new();
+    /// translations.insert("casa".to_owned(), vec!["house".to_owned(), "home".to_owned()]);
+    ///
+    /// let bilingual = Dictionary::new(words).translated(&Translations::new(translations));
+    /// assert_eq!(bilingual.len(), 1);
+    /// assert_eq!(bilingual.definitions("CASA").unwrap(), ["house", "home"]);
+    /// ```
+    pub fn translated(&self, translations: &Translations) -> Dictionary {
+        let words = self.words.keys()
+            .filter_map(|word| translations.get(word).map(|t| (word.clone(), t.to_vec())))
+            .collect();
+        Dictionary::new(words)
+    }
+
+    /// Build the index (length -> words) used by the solver
+    pub fn index(&self) -> WordIndex<'_> {
+        let mut words_len: WordIndex = HashMap::new();
+        for word in self.words() {
+            words_len.entry(word.chars().count()).or_default().push(word);
+        }
+        words_len
+    }
+}
+
+
+/// Translations of words into another language, used as clues of bilingual crosswords.
+/// The words are stored as answers: upper case letters without spaces or punctuation
+#[derive(Debug, Clone, Default)]
+pub struct Translations {
+    words: BTreeMap<String, Vec<String>>,
+    /// the words as they are written in the translations, for each answer
+    spellings: BTreeMap<String, Vec<String>>,
+}
+
+
+impl Translations {
+    pub fn new(translations: BTreeMap<String, Vec<String>>) -> Translations {
+        let mut words: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        let mut spellings: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (word, list) in translations {
+            let answer = Translations::answer(&word);
+            let entry = words.entry(answer.clone()).or_default();
+            for translation in list {
+                if !entry.contains(&translation) {
+                    entry.push(translation);
+                }
+            }
+            let spelling = spellings.entry(answer).or_default();
+            if !spelling.contains(&word) {
+                spelling.push(word);
+            }
+        }
+        words.retain(|word, list| !word.is_empty() && !list.is_empty());
+        spellings.retain(|word, _| words.contains_key(word));
+        Translations { words, spellings }
+    }
+
+    /// Read the translations from a json file: an object mapping each word to the list of its translations
+    pub fn load(path: &str) -> Result<Translations, Error> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| Error::Dictionary { path: path.to_owned(), reason: e.to_string() })?;
+        let translations: BTreeMap<String, Vec<String>> = serde_json::from_str(&content)
+            .map_err(|e| Error::Dictionary { path: path.to_owned(), reason: format!("JSON was not well-formatted: {}", e) })?;
+        Ok(Translations::new(translations))
+    }
+
+    // Word as written in the grid
+    fn answer(word: &str) -> String {
+        word.chars().filter(|c| c.is_alphabetic()).flat_map(char::to_uppercase).collect()
+    }
+
+    /// Translations in the other direction: each translation gets the words it translates,
+    /// written as in the original translations
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use crosswords_generator::dictionary::Translations;
+    ///
+    /// let mut translations = BTreeMap::new();
+    /// translations.insert("fare la spesa".to_owned(), vec!["shopping".to_owned()]);
+    ///
+    /// let reversed = Translations::new(translations).reversed();
+    /// assert_eq!(reversed.get("SHOPPING").unwrap(), ["fare la spesa"]);
+    /// ```
+    pub fn reversed(&self) -> Translations {
+        let mut reversed: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (word, list) in &self.words {
+            let spellings = self.spellings.get(word).map_or(&[][..], Vec::as_slice);
+            for translation in list {
+                reversed.entry(translation.clone()).or_default().extend(spellings.iter().cloned());
+            }
+        }
+        Translations::new(reversed)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Get the translations of a word
+    pub fn get(&self, word: &str) -> Option<&[String]> {
+        self.words.get(&Translations::answer(word)).map(Vec::as_slice)
+    }
+}
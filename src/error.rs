@@ -0,0 +1,63 @@
+use std::fmt;
+
+
+/// Errors of the generator, each group has its own exit code in the CLI
+#[derive(Debug)]
+pub enum Error {
+    /// dictionary missing, unreadable or not well formatted
+    Dictionary { path: String, reason: String },
+    /// word used in the board without definitions in the dictionary
+    MissingDefinitions(String),
+    /// project or board file unreadable or not well formatted
+    BoardFormat { path: String, reason: String },
+    /// coordinate outside of the board
+    OutOfBounds { x: usize, y: usize, width: usize, height: usize },
+    /// unable to write a project, report or exported file
+    Export { path: String, reason: String },
+    /// the board cannot be filled
+    Solver(String),
+    /// the window cannot be created
+    Gui(String),
+}
+
+
+impl Error {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::Dictionary { .. } | Error::MissingDefinitions(_) => 3,
+            Error::BoardFormat { .. } | Error::OutOfBounds { .. } => 4,
+            Error::Export { .. } => 5,
+            Error::Solver(_) => 6,
+            Error::Gui(_) => 7,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Error::Dictionary { .. } | Error::MissingDefinitions(_) => "Dictionary error",
+            Error::BoardFormat { .. } | Error::OutOfBounds { .. } => "Board error",
+            Error::Export { .. } => "Export error",
+            Error::Solver(_) => "Solver error",
+            Error::Gui(_) => "GUI error",
+        }
+    }
+}
+
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Dictionary { path, reason } => write!(f, "Unable to load dictionary '{}': {}", path, reason),
+            Error::MissingDefinitions(word) => write!(f, "The word '{}' has no definitions in the dictionary", word),
+            Error::BoardFormat { path, reason } => write!(f, "Unable to load board '{}': {}", path, reason),
+            Error::OutOfBounds { x, y, width, height } =>
+                write!(f, "Cell ({}, {}) is outside of the {}x{} board", x, y, width, height),
+            Error::Export { path, reason } => write!(f, "Unable to write '{}': {}", path, reason),
+            Error::Solver(reason) => write!(f, "{}", reason),
+            Error::Gui(reason) => write!(f, "Unable to start the GUI: {}", reason),
+        }
+    }
+}
+
+
+impl std::error::Error for Error {}
@@ -0,0 +1,471 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use serde_json::{json, Value};
+
+use crate::arrowword::ArrowClue;
+use crate::codeword::Codeword;
+use crate::error::Error;
+use crate::utils::{is_block, Board, Dir, WordPos, CLUE_CELL, OUTSIDE_CELL};
+
+// Size in px of a cell of the exported grid
+const CELL_SIZE: usize = 40;
+const MARGIN: usize = 2;
+const BAR_WIDTH: usize = 5;
+// Text of the clue cells of arrowwords
+const CLUE_FONT_SIZE: usize = 6;
+const CLUE_LINE_CHARS: usize = 12;
+// Rebus cells with more letters get a smaller font
+const REBUS_FONT_CHARS: usize = 2;
+
+
+// Escape the characters with a meaning in XML
+fn escape(c: char) -> String {
+    match c {
+        '<' => "&lt;".to_owned(),
+        '>' => "&gt;".to_owned(),
+        '&' => "&amp;".to_owned(),
+        c => c.to_string(),
+    }
+}
+
+
+// Split the text in lines of at most `width` chars, breaking between words when possible
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            },
+            _ => {
+                let chars: Vec<char> = word.chars().collect();
+                lines.extend(chars.chunks(width).map(|chunk| chunk.iter().collect::<String>()));
+            },
+        }
+    }
+    lines
+}
+
+
+/// Draw the board as an SVG image: black cells, clue numbers and bars as thick lines.
+/// The clue cells of arrowwords hold the given `clues`, numbers are not drawn in arrowwords.
+/// The cells outside the shape of the board are left out, without the border of the grid.
+/// The letters are drawn only with `solution`
+pub fn to_svg(board: &Board, clues: &[ArrowClue], solution: bool) -> String {
+    let numbers = if board.has_clue_cells() {Vec::new()} else {board.get_numbered_words_pos()};
+    draw_grid(board, clues, solution, &numbers)
+}
+
+
+// Draw the grid with the given clue numbers, see to_svg
+fn draw_grid(board: &Board, clues: &[ArrowClue], solution: bool, numbers: &[(usize, WordPos)]) -> String {
+    let (width, height) = (board.width() * CELL_SIZE, board.height() * CELL_SIZE);
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"sans-serif\">\n",
+        width + 2 * MARGIN, height + 2 * MARGIN);
+    svg.push_str(&format!("<g transform=\"translate({}, {})\">\n", MARGIN, MARGIN));
+
+    // cells
+    for y in 0..board.height() {
+        for x in 0..board.width() {
+            let c = board.get(x, y).unwrap_or('#');
+            if board.is_outside(x, y) {
+                continue;
+            }
+            svg.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"black\" stroke-width=\"1\"/>\n",
+                x * CELL_SIZE, y * CELL_SIZE, CELL_SIZE, CELL_SIZE,
+                match c { '#' => "black", CLUE_CELL => "lightgray", _ => "white" }));
+            if solution && board.valid(x, y) && c != ' ' {
+                let letters = board.rebus(x, y).map_or(c.to_string(), str::to_owned);
+                let font_size = CELL_SIZE * 3 / 5 * REBUS_FONT_CHARS / letters.chars().count().max(REBUS_FONT_CHARS);
+                svg.push_str(&format!(
+                    "<text x=\"{}\" y=\"{}\" font-size=\"{}\" text-anchor=\"middle\">{}</text>\n",
+                    x * CELL_SIZE + CELL_SIZE / 2, y * CELL_SIZE + CELL_SIZE * 3 / 4 + 2, font_size,
+                    letters.chars().map(escape).collect::<String>()));
+            }
+        }
+    }
+
+    // clues of the arrowwords, the cell is shared by its clues
+    for clue in clues {
+        let in_cell: Vec<&ArrowClue> = clues.iter().filter(|c| (c.x, c.y) == (clue.x, clue.y)).collect();
+        let part = in_cell.iter().position(|c| c.pos == clue.pos).unwrap_or(0);
+        let part_height = CELL_SIZE / in_cell.len();
+        let max_lines = (part_height / (CLUE_FONT_SIZE + 1)).max(1);
+        if part > 0 {
+            let y = clue.y * CELL_SIZE + part * part_height;
+            svg.push_str(&format!("<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"black\" stroke-width=\"0.5\"/>\n",
+                clue.x * CELL_SIZE, y, (clue.x + 1) * CELL_SIZE, y));
+        }
+        let text = format!("{} {}", clue.arrow.symbol(), clue.text);
+        for (k, line) in wrap(&text, CLUE_LINE_CHARS).iter().take(max_lines).enumerate() {
+            svg.push_str(&format!("<text x=\"{}\" y=\"{}\" font-size=\"{}\">{}</text>\n",
+                clue.x * CELL_SIZE + 2, clue.y * CELL_SIZE + part * part_height + (k + 1) * (CLUE_FONT_SIZE + 1),
+                CLUE_FONT_SIZE, line.chars().map(escape).collect::<String>()));
+        }
+    }
+
+    // clue numbers, once per cell
+    let mut last = None;
+    for &(number, word_pos) in numbers {
+        if last == Some(number) {
+            continue;
+        }
+        last = Some(number);
+        svg.push_str(&format!("<text x=\"{}\" y=\"{}\" font-size=\"{}\">{}</text>\n",
+            word_pos.x * CELL_SIZE + 3, word_pos.y * CELL_SIZE + CELL_SIZE / 4 + 1, CELL_SIZE / 4, number));
+    }
+
+    // bars
+    for y in 0..board.height() {
+        for x in 0..board.width() {
+            if board.has_bar(x, y, Dir::HOR) && x + 1 < board.width() {
+                let bx = (x + 1) * CELL_SIZE;
+                svg.push_str(&format!("<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"black\" stroke-width=\"{}\"/>\n",
+                    bx, y * CELL_SIZE, bx, (y + 1) * CELL_SIZE, BAR_WIDTH));
+            }
+            if board.has_bar(x, y, Dir::VER) && y + 1 < board.height() {
+                let by = (y + 1) * CELL_SIZE;
+                svg.push_str(&format!("<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"black\" stroke-width=\"{}\"/>\n",
+                    x * CELL_SIZE, by, (x + 1) * CELL_SIZE, by, BAR_WIDTH));
+            }
+        }
+    }
+
+    // border, only for rectangular boards
+    if !board.has_outside_cells() {
+        svg.push_str(&format!("<rect x=\"0\" y=\"0\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"black\" stroke-width=\"{}\"/>\n",
+            width, height, MARGIN * 2));
+    }
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
+
+
+/// Write the board as an SVG file, see [`to_svg`]
+pub fn save_svg(board: &Board, clues: &[ArrowClue], solution: bool, path: &str) -> Result<(), Error> {
+    fs::write(path, to_svg(board, clues, solution)).map_err(|e| Error::Export { path: path.to_owned(), reason: e.to_string() })
+}
+
+
+/// Draw a codeword puzzle as an SVG image: the number of each cell, the revealed letters
+/// and, below the grid, the letter key
+pub fn codeword_svg(board: &Board, codeword: &Codeword) -> String {
+    let key = codeword.key();
+    let key_columns = board.width().max(13);
+    let key_rows = key.len().div_ceil(key_columns);
+    let width = board.width().max(key_columns) * CELL_SIZE;
+    let height = (board.height() + 1 + key_rows) * CELL_SIZE;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"sans-serif\">\n",
+        width + 2 * MARGIN, height + 2 * MARGIN);
+    svg.push_str(&format!("<g transform=\"translate({}, {})\">\n", MARGIN, MARGIN));
+
+    // a numbered cell with its letter, if revealed
+    let mut cell = |x: usize, y: usize, number: Option<usize>, letter: Option<char>| {
+        svg.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"black\" stroke-width=\"1\"/>\n",
+            x * CELL_SIZE, y * CELL_SIZE, CELL_SIZE, CELL_SIZE, if number.is_some() {"white"} else {"black"}));
+        if let Some(number) = number {
+            svg.push_str(&format!("<text x=\"{}\" y=\"{}\" font-size=\"{}\">{}</text>\n",
+                x * CELL_SIZE + 3, y * CELL_SIZE + CELL_SIZE / 4 + 1, CELL_SIZE / 4, number));
+        }
+        if let Some(c) = letter {
+            svg.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\" font-size=\"{}\" text-anchor=\"middle\">{}</text>\n",
+                x * CELL_SIZE + CELL_SIZE / 2, y * CELL_SIZE + CELL_SIZE * 3 / 4 + 2, CELL_SIZE * 3 / 5, escape(c)));
+        }
+    };
+
+    for y in 0..board.height() {
+        for x in (0..board.width()).filter(|&x| !board.is_outside(x, y)) {
+            let number = codeword.number(board, x, y);
+            let letter = board.get(x, y).ok().filter(|c| codeword.revealed.contains(c));
+            cell(x, y, number, letter);
+        }
+    }
+    for (k, (number, letter)) in key.into_iter().enumerate() {
+        cell(k % key_columns, board.height() + 1 + k / key_columns, Some(number), letter);
+    }
+
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
+
+
+/// Write a codeword puzzle as an SVG file, see [`codeword_svg`]
+pub fn save_codeword_svg(board: &Board, codeword: &Codeword, path: &str) -> Result<(), Error> {
+    fs::write(path, codeword_svg(board, codeword)).map_err(|e| Error::Export { path: path.to_owned(), reason: e.to_string() })
+}
+
+
+/// Word list of a fill-in puzzle as text, grouped by length
+pub fn word_list(words: &BTreeMap<usize, Vec<String>>) -> String {
+    words.iter()
+        .map(|(len, list)| format!("{} LETTERS\n{}\n", len, list.join("\n")))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+
+/// Write the word list of a fill-in puzzle, see [`word_list`]
+pub fn save_word_list(words: &BTreeMap<usize, Vec<String>>, path: &str) -> Result<(), Error> {
+    fs::write(path, word_list(words)).map_err(|e| Error::Export { path: path.to_owned(), reason: e.to_string() })
+}
+
+
+// Clue of a word, empty without a definition
+fn clue_text<'a>(definitions: &'a [(WordPos, String)], word_pos: &WordPos) -> &'a str {
+    definitions.iter().find(|(wp, _)| wp == word_pos).map_or("", |(_, def)| def.as_str())
+}
+
+
+/// Puzzle in the ipuz format (JSON): the numbered grid with bars, the solution with
+/// the letters of the rebus cells and the clues. Clue cells are blocks, the cells
+/// outside the shape of the board are omitted
+pub fn to_ipuz(board: &Board, definitions: &[(WordPos, String)], title: &str) -> String {
+    let numbered = board.get_numbered_words_pos();
+    let number_at = |x: usize, y: usize| numbered.iter().find(|(_, wp)| (wp.x, wp.y) == (x, y)).map(|(n, _)| *n);
+
+    let mut puzzle: Vec<Vec<Value>> = Vec::new();
+    let mut solution: Vec<Vec<Value>> = Vec::new();
+    for y in 0..board.height() {
+        let mut puzzle_row = Vec::new();
+        let mut solution_row = Vec::new();
+        for x in 0..board.width() {
+            let c = board.get(x, y).unwrap_or('#');
+            if board.is_outside(x, y) {
+                puzzle_row.push(Value::Null);
+                solution_row.push(Value::Null);
+                continue;
+            }
+            if is_block(c) {
+                puzzle_row.push(json!("#"));
+                solution_row.push(json!("#"));
+                continue;
+            }
+            let cell = json!(number_at(x, y).unwrap_or(0));
+            let barred: String = [(Dir::HOR, 'R'), (Dir::VER, 'B')].iter()
+                .filter(|(dir, _)| board.has_bar(x, y, *dir))
+                .map(|(_, side)| side)
+                .collect();
+            puzzle_row.push(match barred.is_empty() {
+                true => cell,
+                false => json!({ "cell": cell, "style": { "barred": barred } }),
+            });
+            solution_row.push(match (board.rebus(x, y), c) {
+                (Some(letters), _) => json!(letters),
+                (None, ' ') => Value::Null,
+                (None, c) => json!(c.to_string()),
+            });
+        }
+        puzzle.push(puzzle_row);
+        solution.push(solution_row);
+    }
+
+    let clues = |dir: Dir| -> Vec<Value> {
+        numbered.iter()
+            .filter(|(_, wp)| wp.dir == dir)
+            .map(|(n, wp)| json!([n, clue_text(definitions, wp)]))
+            .collect()
+    };
+    let ipuz = json!({
+        "version": "http://ipuz.org/v2",
+        "kind": ["http://ipuz.org/crossword#1"],
+        "title": title,
+        "dimensions": { "width": board.width(), "height": board.height() },
+        "block": "#",
+        "empty": 0,
+        "puzzle": puzzle,
+        "solution": solution,
+        "clues": { "Across": clues(Dir::HOR), "Down": clues(Dir::VER) },
+    });
+    serde_json::to_string_pretty(&ipuz).unwrap_or_default()
+}
+
+
+/// Write the puzzle as an ipuz file, see [`to_ipuz`]
+pub fn save_ipuz(board: &Board, definitions: &[(WordPos, String)], title: &str, path: &str) -> Result<(), Error> {
+    fs::write(path, to_ipuz(board, definitions, title)).map_err(|e| Error::Export { path: path.to_owned(), reason: e.to_string() })
+}
+
+
+// Checksum of the .puz format
+fn puz_checksum(data: &[u8], mut checksum: u16) -> u16 {
+    for &b in data {
+        checksum = checksum.rotate_right(1).wrapping_add(b as u16);
+    }
+    checksum
+}
+
+
+// Text of the .puz format in Latin-1, other chars become '?'
+fn latin1(text: &str) -> Vec<u8> {
+    text.chars().map(|c| u8::try_from(c as u32).unwrap_or(b'?')).collect()
+}
+
+
+// Extra section of the .puz format: name, length, checksum, data and a final 0
+fn puz_section(name: &[u8; 4], data: &[u8]) -> Vec<u8> {
+    let mut section = name.to_vec();
+    section.extend((data.len() as u16).to_le_bytes());
+    section.extend(puz_checksum(data, 0).to_le_bytes());
+    section.extend(data);
+    section.push(0);
+    section
+}
+
+
+/// Puzzle in the Across Lite .puz format (version 1.3): solution, empty grid and clues
+/// sorted by number with across before down. Rebus cells are written in the GRBS and RTBL
+/// sections, the solution grid keeps their first letter. Clue cells and the cells outside
+/// the shape of the board are blocks, bars cannot be represented. The board must be at most 255x255
+pub fn to_puz(board: &Board, definitions: &[(WordPos, String)], title: &str) -> Vec<u8> {
+    let mut solution = Vec::with_capacity(board.width() * board.height());
+    for y in 0..board.height() {
+        for x in 0..board.width() {
+            solution.push(match board.get(x, y).unwrap_or('#') {
+                c if is_block(c) => b'.',
+                ' ' => b'-',
+                c => latin1(&c.to_uppercase().to_string()).first().copied().unwrap_or(b'?'),
+            });
+        }
+    }
+    let grid: Vec<u8> = solution.iter().map(|&c| if c == b'.' {b'.'} else {b'-'}).collect();
+    let clues: Vec<Vec<u8>> = board.get_numbered_words_pos().iter()
+        .map(|(_, wp)| latin1(clue_text(definitions, wp)))
+        .collect();
+    let (title, author, copyright, notes) = (latin1(title), Vec::new(), Vec::new(), Vec::new());
+
+    // checksum of the strings: title, author, copyright and notes with their final 0 when not empty
+    let strings_checksum = |mut checksum: u16| {
+        for text in [&title, &author, &copyright] {
+            if !text.is_empty() {
+                checksum = puz_checksum(text, checksum);
+                checksum = puz_checksum(&[0], checksum);
+            }
+        }
+        for clue in &clues {
+            checksum = puz_checksum(clue, checksum);
+        }
+        if !notes.is_empty() {
+            checksum = puz_checksum(&notes, checksum);
+            checksum = puz_checksum(&[0], checksum);
+        }
+        checksum
+    };
+
+    let mut cib = Vec::with_capacity(8);
+    cib.push(u8::try_from(board.width()).unwrap_or(u8::MAX));
+    cib.push(u8::try_from(board.height()).unwrap_or(u8::MAX));
+    cib.extend((clues.len() as u16).to_le_bytes());
+    cib.extend(1u16.to_le_bytes());
+    cib.extend(0u16.to_le_bytes());
+
+    let cib_checksum = puz_checksum(&cib, 0);
+    let checksum = strings_checksum(puz_checksum(&grid, puz_checksum(&solution, cib_checksum)));
+    let parts = [cib_checksum, puz_checksum(&solution, 0), puz_checksum(&grid, 0), strings_checksum(0)];
+    let masked_low: Vec<u8> = parts.iter().zip(b"ICHE").map(|(part, mask)| mask ^ (part & 0xFF) as u8).collect();
+    let masked_high: Vec<u8> = parts.iter().zip(b"ATED").map(|(part, mask)| mask ^ (part >> 8) as u8).collect();
+
+    let mut puz = Vec::new();
+    puz.extend(checksum.to_le_bytes());
+    puz.extend(b"ACROSS&DOWN\0");
+    puz.extend(cib_checksum.to_le_bytes());
+    puz.extend(masked_low);
+    puz.extend(masked_high);
+    puz.extend(b"1.3\0");
+    puz.extend([0; 2]);
+    puz.extend(0u16.to_le_bytes());
+    puz.extend([0; 12]);
+    puz.extend(&cib);
+    puz.extend(&solution);
+    puz.extend(&grid);
+    for text in [&title, &author, &copyright].into_iter().chain(&clues).chain([&notes]) {
+        puz.extend(text);
+        puz.push(0);
+    }
+
+    // rebus cells: number of the rebus + 1 for each cell and the table of the rebus letters
+    if board.has_rebus() {
+        let mut table: Vec<&str> = Vec::new();
+        let mut cells = vec![0u8; board.width() * board.height()];
+        for (x, y, letters) in board.rebus_cells() {
+            let key = table.iter().position(|&l| l == letters).unwrap_or_else(|| {
+                table.push(letters);
+                table.len() - 1
+            });
+            cells[y * board.width() + x] = key as u8 + 1;
+        }
+        let rtbl: String = table.iter().enumerate()
+            .map(|(key, letters)| format!("{:>2}:{};", key, letters.to_uppercase()))
+            .collect();
+        puz.extend(puz_section(b"GRBS", &cells));
+        puz.extend(puz_section(b"RTBL", &latin1(&rtbl)));
+    }
+    puz
+}
+
+
+/// Write the puzzle as an Across Lite .puz file, see [`to_puz`]
+pub fn save_puz(board: &Board, definitions: &[(WordPos, String)], title: &str, path: &str) -> Result<(), Error> {
+    let export_error = |reason: String| Error::Export { path: path.to_owned(), reason };
+    if board.width() > u8::MAX as usize || board.height() > u8::MAX as usize {
+        return Err(export_error(format!("a .puz board cannot be larger than {}x{}", u8::MAX, u8::MAX)));
+    }
+    fs::write(path, to_puz(board, definitions, title)).map_err(|e| export_error(e.to_string()))
+}
+
+
+/// Empty grid of a diagramless puzzle as an SVG image: the black cells are not shown, the
+/// solver finds them from the clues. With `hint` the starting square of the first clue holds
+/// its number. The solution page is drawn by [`to_svg`] with the same numbers
+pub fn diagramless_svg(board: &Board, hint: bool) -> String {
+    let mut grid = Board::new(board.width(), board.height());
+    for y in 0..board.height() {
+        for x in 0..board.width() {
+            if board.is_outside(x, y) {
+                let _ = grid.set(x, y, OUTSIDE_CELL);
+            }
+        }
+    }
+    let numbers: Vec<(usize, WordPos)> = match hint {
+        true => board.get_numbered_words_pos().into_iter().take(1).collect(),
+        false => Vec::new(),
+    };
+    draw_grid(&grid, &[], false, &numbers)
+}
+
+
+/// Clue list of a diagramless puzzle as text, numbered as the filled board. With `hint`
+/// each clue tells its starting square (row and column, from 1)
+pub fn diagramless_clues(board: &Board, definitions: &[(WordPos, String)], hint: bool) -> String {
+    let numbered = board.get_numbered_words_pos();
+    let mut text = format!("DIAGRAMLESS {}x{}\n", board.width(), board.height());
+    for (dir, title) in [(Dir::HOR, "ACROSS"), (Dir::VER, "DOWN")] {
+        text.push_str(&format!("\n{}\n", title));
+        for (number, word_pos) in numbered.iter().filter(|(_, wp)| wp.dir == dir) {
+            text.push_str(&format!("{}. {}", number, clue_text(definitions, word_pos)));
+            if hint {
+                text.push_str(&format!(" (row {}, column {})", word_pos.y + 1, word_pos.x + 1));
+            }
+            text.push('\n');
+        }
+    }
+    text
+}
+
+
+/// Write the empty grid of a diagramless puzzle as an SVG file, see [`diagramless_svg`]
+pub fn save_diagramless_svg(board: &Board, hint: bool, path: &str) -> Result<(), Error> {
+    fs::write(path, diagramless_svg(board, hint)).map_err(|e| Error::Export { path: path.to_owned(), reason: e.to_string() })
+}
+
+
+/// Write the clue list of a diagramless puzzle, see [`diagramless_clues`]
+pub fn save_diagramless_clues(board: &Board, definitions: &[(WordPos, String)], hint: bool, path: &str) -> Result<(), Error> {
+    fs::write(path, diagramless_clues(board, definitions, hint)).map_err(|e| Error::Export { path: path.to_owned(), reason: e.to_string() })
+}
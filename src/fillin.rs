@@ -0,0 +1,174 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::dictionary::WordIndex;
+use crate::error::Error;
+use crate::generator::{find_solutions, GenerateOptions};
+use crate::utils::{is_block, Board};
+
+
+/// Fill-in puzzle: the grid to fill, with the starter letters, and the list of its words
+#[derive(Debug, Clone)]
+pub struct FillIn {
+    /// empty grid, the starter letters are already written
+    pub grid: Board,
+    /// words of the solution grouped by length, each one sorted alphabetically
+    pub words: BTreeMap<usize, Vec<String>>,
+    /// cells with a starter letter
+    pub starters: Vec<(usize, usize)>,
+    /// the word list leads to a single solution
+    pub unique: bool,
+}
+
+
+/// Find up to `limit` ways of filling the board using each word of the list once,
+/// as the solver of a fill-in puzzle does. A word listed twice is used twice.
+/// The letters already on the board are kept, rebus cells of blanks are filled with
+/// as many letters as they hold
+///
+/// ```
+/// use crosswords_generator::fillin::solve_fill_in;
+/// use crosswords_generator::Board;
+///
+/// // the two words of each direction can be swapped
+/// let words: Vec<String> = ["AB", "CD", "AC", "BD"].iter().map(|w| w.to_string()).collect();
+/// assert_eq!(solve_fill_in(&Board::new(2, 2), &words, 10).unwrap().len(), 2);
+/// ```
+pub fn solve_fill_in(board: &Board, words: &[String], limit: usize) -> Result<Vec<Board>, Error> {
+    // the list is the dictionary of the solver, the copies of a word allow it more times
+    let mut words_len: WordIndex = HashMap::new();
+    for word in words {
+        words_len.entry(word.chars().count()).or_default().push(word);
+    }
+    for list in words_len.values_mut() {
+        list.sort_unstable();
+    }
+    find_solutions(board, &words_len, &GenerateOptions::new(), limit)
+}
+
+
+// Copy the content of the cell from the solution, with the letters of a rebus cell
+fn reveal(grid: &mut Board, solution: &Board, x: usize, y: usize) {
+    match solution.rebus(x, y) {
+        Some(letters) => {
+            let _ = grid.set_rebus(x, y, letters);
+        },
+        None => {
+            let _ = grid.set(x, y, solution.get(x, y).unwrap_or(' '));
+        },
+    }
+}
+
+
+/// Build a fill-in puzzle from a filled board and check that its word list has a single solution.
+/// While there are other solutions, up to `max_starters` letters of the board are given as
+/// starters, choosing a cell where another solution differs. The search runs the solver of
+/// [`generate`](crate::generate) with the answers as dictionary, see [`solve_fill_in`].
+/// The rebus cells stay in the grid without their letters
+///
+/// ```
+/// use crosswords_generator::fillin::fill_in;
+/// use crosswords_generator::Board;
+///
+/// let rows: Vec<String> = ["CART", "O##O", "W##E"].iter().map(|r| r.to_string()).collect();
+/// let mut solution = Board::from_rows(&rows);
+/// solution.set_rebus(3, 2, "ES").unwrap();
+///
+/// let puzzle = fill_in(&solution, 0).unwrap();
+/// assert!(puzzle.unique);
+/// assert_eq!(puzzle.grid.rebus(3, 2), Some("  "));
+/// assert_eq!(puzzle.words[&4], ["CART", "TOES"]);
+/// ```
+pub fn fill_in(solution: &Board, max_starters: usize) -> Result<FillIn, Error> {
+    let answers: Vec<String> = solution.get_words_pos().iter().map(|wp| solution.get_word(wp)).collect();
+
+    let mut grid = solution.clone();
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            if let Some(letters) = solution.rebus(x, y) {
+                let _ = grid.set_rebus(x, y, &" ".repeat(letters.chars().count()));
+            }
+            else if grid.get(x, y).is_ok_and(|c| !is_block(c)) {
+                let _ = grid.set(x, y, ' ');
+            }
+        }
+    }
+
+    // unique when the solution is the only filling of the grid
+    let mut starters = Vec::new();
+    let unique = loop {
+        let solutions = solve_fill_in(&grid, &answers, 2)?;
+        let Some(other) = solutions.iter().find(|board| *board != solution) else {
+            break !solutions.is_empty();
+        };
+        if starters.len() >= max_starters {
+            break false;
+        }
+        let cell = (0..grid.height())
+            .flat_map(|y| (0..grid.width()).map(move |x| (x, y)))
+            .find(|&(x, y)| other.get(x, y).ok() != solution.get(x, y).ok() || other.rebus(x, y) != solution.rebus(x, y));
+        let Some((x, y)) = cell else {
+            break false;
+        };
+        reveal(&mut grid, solution, x, y);
+        starters.push((x, y));
+    };
+
+    let mut words: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+    for word in answers {
+        words.entry(word.chars().count()).or_default().push(word);
+    }
+    for list in words.values_mut() {
+        list.sort();
+    }
+
+    Ok(FillIn { grid, words, starters, unique })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(words: &[&str]) -> Vec<String> {
+        words.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn no_solution() {
+        // four slots and three copies of the only word
+        assert!(solve_fill_in(&Board::new(2, 2), &list(&["AA", "AA", "AA"]), 2).unwrap().is_empty());
+        // no words of length 3
+        assert!(solve_fill_in(&Board::new(3, 3), &list(&["AB", "CD"]), 2).is_err());
+    }
+
+    #[test]
+    fn repeated_answers() {
+        // AB/AB with AA and BB down, or its transpose: no word is used more than listed
+        let solutions = solve_fill_in(&Board::new(2, 2), &list(&["AB", "AB", "AA", "BB"]), 10).unwrap();
+        assert_eq!(solutions.len(), 2);
+
+        let solution = Board::from_rows(&list(&["AB", "AB"]));
+        let puzzle = fill_in(&solution, 1).unwrap();
+        assert!(puzzle.unique);
+        assert_eq!(puzzle.starters.len(), 1);
+        assert_eq!(puzzle.words[&2], ["AA", "AB", "AB", "BB"]);
+    }
+
+    #[test]
+    fn rebus_cells() {
+        // the starter C rules out the transposed filling
+        let mut grid = Board::new(2, 2);
+        grid.set(0, 1, 'C').unwrap();
+        grid.set_rebus(1, 1, "  ").unwrap();
+        let solutions = solve_fill_in(&grid, &list(&["AB", "CDE", "AC", "BDE"]), 10).unwrap();
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].rebus(1, 1), Some("DE"));
+
+        // the transposed filling is the same board
+        let mut solution = Board::from_rows(&list(&["AB", "BA"]));
+        solution.set_rebus(1, 1, "AA").unwrap();
+        let puzzle = fill_in(&solution, 0).unwrap();
+        assert!(puzzle.unique);
+        assert_eq!(puzzle.grid.rebus(1, 1), Some("  "));
+    }
+}
@@ -0,0 +1,656 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+use crate::dictionary::{Dictionary, WordIndex};
+use crate::error::Error;
+use crate::utils::{is_block, Board, Dir, Solution, WordPos};
+
+static EMPTY_VEC: Vec<&str> = Vec::new();
+
+// Number of visited nodes between two checks of the progress timer
+const PROGRESS_CHECK_NODES: u64 = 256;
+
+
+/// Word square puzzles, boards without black squares
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WordSquare {
+    /// any board
+    #[default]
+    None,
+    /// square board where row i is the same word as column i
+    Single,
+    /// rows and columns are all different words
+    Double,
+}
+
+
+/// Options of [`generate`], built with chained setters
+///
+/// ```
+/// use crosswords_generator::GenerateOptions;
+///
+/// let options = GenerateOptions::new().shuffle(true).seed(42);
+/// assert!(!options.repeat_words);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenerateOptions {
+    /// Shuffle the candidates before filling, otherwise they are tried in dictionary order
+    pub shuffle: bool,
+    /// Seed of the shuffle
+    pub seed: u64,
+    /// Allow the same word more than once in the board
+    pub repeat_words: bool,
+    /// Minimum time in ms between two [`Progress`] snapshots
+    pub progress_interval: u64,
+    /// Fill the board as a word square
+    pub square: WordSquare,
+}
+
+
+impl Default for GenerateOptions {
+    fn default() -> GenerateOptions {
+        GenerateOptions {
+            shuffle: false,
+            seed: 0,
+            repeat_words: false,
+            progress_interval: 100,
+            square: WordSquare::None,
+        }
+    }
+}
+
+
+impl GenerateOptions {
+    pub fn new() -> GenerateOptions {
+        GenerateOptions::default()
+    }
+
+    pub fn shuffle(mut self, shuffle: bool) -> GenerateOptions {
+        self.shuffle = shuffle;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> GenerateOptions {
+        self.seed = seed;
+        self
+    }
+
+    pub fn repeat_words(mut self, repeat_words: bool) -> GenerateOptions {
+        self.repeat_words = repeat_words;
+        self
+    }
+
+    pub fn progress_interval(mut self, progress_interval: u64) -> GenerateOptions {
+        self.progress_interval = progress_interval;
+        self
+    }
+
+    pub fn square(mut self, square: WordSquare) -> GenerateOptions {
+        self.square = square;
+        self
+    }
+}
+
+
+/// Snapshot of a running search, published by [`generate_with_progress`]
+#[derive(Debug, Clone)]
+pub struct Progress {
+    /// Partially filled board
+    pub board: Board,
+    /// Number of words placed
+    pub depth: usize,
+    /// Number of words to place
+    pub words: usize,
+    pub visited_nodes: u64,
+    /// Nodes visited per second since the previous snapshot
+    pub nodes_per_sec: f64,
+    /// Number of patterns in the cache of valid words
+    pub cache_size: usize,
+    pub time_elapsed: u128,
+}
+
+
+// State shared by the recursive calls of fill_board
+struct SearchState<'a, 'p> {
+    // times each word is on the board, a word can be used as many times as it is in the index
+    words_used: HashMap<&'a str, usize>,
+    // words listed more than once in the index, with the number of copies
+    max_uses: HashMap<&'a str, usize>,
+    words_map_cache: HashMap<String, Vec<&'a str>>,
+    visited_nodes: u64,
+    rep_words: bool,
+    words: usize,
+    time_fill: Instant,
+    last_progress: Instant,
+    last_visited_nodes: u64,
+    progress_interval: Duration,
+    progress: &'p mut dyn FnMut(&Progress) -> bool,
+    stopped: bool,
+    // word positions filled together with the same word (word squares)
+    partners: HashMap<WordPos, WordPos>,
+    // filled boards found so far, the search ends when max_solutions are found
+    solutions: Vec<Board>,
+    max_solutions: usize,
+    // double word squares: a square is not kept when its transpose was already found
+    skip_transposed: bool,
+}
+
+
+impl SearchState<'_, '_> {
+    // Publish a snapshot of the search, the search stops if the callback returns false
+    fn publish(&mut self, board: &Board, depth: usize) {
+        let seconds = self.last_progress.elapsed().as_secs_f64();
+        let progress = Progress {
+            board: board.clone(),
+            depth,
+            words: self.words,
+            visited_nodes: self.visited_nodes,
+            nodes_per_sec: if seconds > 0.0 { (self.visited_nodes - self.last_visited_nodes) as f64 / seconds } else { 0.0 },
+            cache_size: self.words_map_cache.len(),
+            time_elapsed: self.time_fill.elapsed().as_millis(),
+        };
+        self.last_progress = Instant::now();
+        self.last_visited_nodes = self.visited_nodes;
+        if !(self.progress)(&progress) {
+            self.stopped = true;
+        }
+    }
+}
+
+
+/// Fill the open cells of the board with words of the index, keeping the letters already on the board.
+///
+/// Without `repeat_words` a word is used at most as many times as it is listed in the index.
+///
+/// Returns an error if some word position has no candidates of its length, otherwise the [`Solution`]
+/// tells if the board was filled. When no solution is found the board is left unchanged.
+pub fn generate(board: &mut Board, words_len: &WordIndex, options: &GenerateOptions) -> Result<Solution, Error> {
+    generate_with_progress(board, words_len, options, |_| true)
+}
+
+
+/// Same as [`generate`], calling `progress` with a snapshot of the search at most every
+/// `options.progress_interval` ms. The search is stopped when `progress` returns false.
+pub fn generate_with_progress<F>(board: &mut Board, words_len: &WordIndex, options: &GenerateOptions,
+                                    mut progress: F) -> Result<Solution, Error>
+where F: FnMut(&Progress) -> bool {
+    search(board, words_len, options, &mut progress, 1).map(|(solution, _)| solution)
+}
+
+
+/// Find up to `limit` word squares of the given size, see [`WordSquare`].
+/// The double word squares are reported once, without their transposed square
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use crosswords_generator::{word_squares, Dictionary, GenerateOptions, WordSquare};
+///
+/// let mut words = BTreeMap::new();
+/// for word in ["AB", "BA", "BC", "CB", "AC"] {
+///     words.insert(word.to_owned(), vec![]);
+/// }
+/// let dictionary = Dictionary::new(words);
+///
+/// let options = GenerateOptions::new().square(WordSquare::Single);
+/// let squares = word_squares(2, 2, &dictionary.index(), &options, 100).unwrap();
+/// assert_eq!(squares.len(), 8);
+/// assert!(squares.iter().all(|square| square.get(1, 0).unwrap() == square.get(0, 1).unwrap()));
+/// ```
+pub fn word_squares(width: usize, height: usize, words_len: &WordIndex, options: &GenerateOptions,
+                    limit: usize) -> Result<Vec<Board>, Error> {
+    if options.square == WordSquare::None {
+        return Err(Error::Solver("Choose a single or double word square".to_owned()));
+    }
+    if limit == 0 {
+        return Ok(Vec::new());
+    }
+    let mut board = Board::new(width, height);
+    let (_, squares) = search(&mut board, words_len, options, &mut |_| true, limit)?;
+    Ok(squares)
+}
+
+
+/// Find up to `limit` different fillings of the board, keeping the letters already on the board.
+/// The board is not changed. Returns an error if some word position has no candidates of its length
+pub fn find_solutions(board: &Board, words_len: &WordIndex, options: &GenerateOptions,
+                      limit: usize) -> Result<Vec<Board>, Error> {
+    if limit == 0 {
+        return Ok(Vec::new());
+    }
+    let mut board = board.clone();
+    let (_, solutions) = search(&mut board, words_len, options, &mut |_| true, limit)?;
+    Ok(solutions)
+}
+
+
+// Check if the board is the other one with rows and columns swapped
+fn is_transposed(a: &Board, b: &Board) -> bool {
+    a.width() == b.height() && a.height() == b.width()
+        && (0..a.height()).all(|y| (0..a.width()).all(|x| a.get(x, y).ok() == b.get(y, x).ok()))
+}
+
+
+// Fill the board stopping after max_solutions filled boards, the board keeps the last one
+fn search(board: &mut Board, words_len: &WordIndex, options: &GenerateOptions,
+          progress: &mut dyn FnMut(&Progress) -> bool, max_solutions: usize) -> Result<(Solution, Vec<Board>), Error> {
+    // Word squares have no black squares, row i and column i are coupled in the single ones
+    if options.square != WordSquare::None {
+        if (0..board.height()).any(|y| (0..board.width()).any(|x| !board.valid(x, y))) || board.has_bars() || board.has_rebus() {
+            return Err(Error::Solver("A word square cannot have black squares, bars or rebus cells".to_owned()));
+        }
+        if options.square == WordSquare::Single && board.width() != board.height() {
+            return Err(Error::Solver(format!("A word square must be square, not {}x{}", board.width(), board.height())));
+        }
+    }
+
+    // Randomize words
+    let mut words_len = words_len.clone();
+    if options.shuffle {
+        let mut rng = StdRng::seed_from_u64(options.seed);
+        for words in words_len.values_mut() {
+            words.shuffle(&mut rng);
+        }
+    }
+
+    // The copies of a word are removed from the index, they are counted as allowed uses of the word
+    let mut max_uses: HashMap<&str, usize> = HashMap::new();
+    if !options.repeat_words {
+        let mut seen: HashSet<&str> = HashSet::new();
+        for words in words_len.values_mut() {
+            words.retain(|&word| {
+                if seen.insert(word) {
+                    return true;
+                }
+                *max_uses.entry(word).or_insert(1) += 1;
+                false
+            });
+        }
+    }
+
+    // Create list of missing word positions
+    let mut words_pos = board.get_words_pos();
+    words_pos.sort_by(|a, b| Ord::cmp(&a.len, &b.len));
+
+    // Every word must have at least a candidate of the same length, rebus cells count all their letters
+    if let Some(word_pos) = words_pos.iter().find(|wp| words_len.get(&board.word_len(wp)).is_none_or(|words| words.is_empty())) {
+        return Err(Error::Solver(format!("No words of length {} in the dictionary for the word at ({}, {})",
+            board.word_len(word_pos), word_pos.x, word_pos.y)));
+    }
+
+    // create map of word_pos -> intersecting word_pos
+    let mut words_intersect: HashMap<&WordPos, Vec<&WordPos>> = HashMap::new();
+    for word_pos in &words_pos {
+        match word_pos.dir {
+            Dir::HOR => {
+                let wi: Vec<&WordPos> = words_pos.iter().filter(|wp|
+                    wp.dir == Dir::VER &&
+                    wp.y <= word_pos.y &&
+                    wp.y + wp.len > word_pos.y).collect();
+                words_intersect.insert(word_pos, wi);
+            },
+            Dir::VER => {
+                let wi: Vec<&WordPos> = words_pos.iter().filter(|wp|
+                        wp.dir == Dir::HOR &&
+                        wp.x <= word_pos.x &&
+                        wp.x + wp.len > word_pos.x).collect();
+                words_intersect.insert(word_pos, wi);
+            }
+        }
+    }
+
+    // only the rows are searched in single word squares, each one fills its column too
+    let mut partners = HashMap::new();
+    let mut search_pos = words_pos.clone();
+    if options.square == WordSquare::Single {
+        for word_pos in words_pos.iter().filter(|wp| wp.dir == Dir::HOR) {
+            partners.insert(*word_pos, WordPos::new(word_pos.y, 0, Dir::VER, word_pos.len));
+        }
+        search_pos.retain(|wp| wp.dir == Dir::HOR);
+    }
+
+    // fill board
+    let mut state = SearchState {
+        words_used: HashMap::with_capacity(words_pos.len()),
+        max_uses,
+        words_map_cache: HashMap::new(),
+        visited_nodes: 0,
+        rep_words: options.repeat_words && options.square != WordSquare::Double,
+        words: search_pos.len(),
+        time_fill: Instant::now(),
+        last_progress: Instant::now(),
+        last_visited_nodes: 0,
+        progress_interval: Duration::from_millis(options.progress_interval),
+        progress,
+        stopped: false,
+        partners,
+        solutions: Vec::new(),
+        max_solutions,
+        skip_transposed: options.square == WordSquare::Double,
+    };
+
+    // recursive
+    let found = fill_board(board, &words_len, &search_pos, &words_intersect, &mut state);
+
+    // the board keeps the last solution when more are searched
+    if !found {
+        if let Some(last) = state.solutions.last() {
+            *board = last.clone();
+        }
+    }
+
+    Ok((Solution {
+        found: !state.solutions.is_empty(),
+        cancelled: state.stopped,
+        time_elapsed: state.time_fill.elapsed().as_millis(),
+        visited_nodes: state.visited_nodes,
+    }, state.solutions))
+}
+
+
+/// Clear the given cells and fill them again, keeping every other letter of the board.
+///
+/// The words entirely made of cleared cells get a different answer than before, the words
+/// only partially cleared may keep it. When no solution is found the board is left unchanged.
+pub fn regenerate(board: &mut Board, cells: &[(usize, usize)], words_len: &WordIndex,
+                    options: &GenerateOptions) -> Result<Solution, Error> {
+    regenerate_with_progress(board, cells, words_len, options, |_| true)
+}
+
+
+/// Same as [`regenerate`], reporting the progress as [`generate_with_progress`]
+pub fn regenerate_with_progress<F>(board: &mut Board, cells: &[(usize, usize)], words_len: &WordIndex,
+                                    options: &GenerateOptions, progress: F) -> Result<Solution, Error>
+where F: FnMut(&Progress) -> bool {
+    // Previous answers of the words inside the cleared cells
+    let previous: HashSet<String> = board.get_words_pos().iter()
+        .filter(|wp| (0..wp.len).all(|k| cells.contains(&wp.cell(k))))
+        .map(|wp| board.get_word(wp))
+        .filter(|word| !word.contains(' '))
+        .collect();
+
+    let original = board.clone();
+    for &(x, y) in cells {
+        if !is_block(board.get(x, y)?) && board.rebus(x, y).is_none() {
+            board.set(x, y, ' ')?;
+        }
+    }
+
+    // Words still complete on the board must remain available
+    let kept: HashSet<String> = board.get_words_pos().iter()
+        .map(|wp| board.get_word(wp))
+        .filter(|word| !word.contains(' '))
+        .collect();
+    let mut words_len = words_len.clone();
+    for words in words_len.values_mut() {
+        words.retain(|word| !previous.contains(*word) || kept.contains(*word));
+    }
+
+    let sol = generate_with_progress(board, &words_len, options, progress);
+    if !sol.as_ref().is_ok_and(|sol| sol.found) {
+        *board = original;
+    }
+    sol
+}
+
+
+fn fill_board<'a>(board: &mut Board, words_len: &'a HashMap<usize, Vec<&'a str>>, words_pos: &[WordPos],
+                    words_intersect: &HashMap<&WordPos, Vec<&WordPos>>, state: &mut SearchState<'a, '_>) -> bool {
+    if words_pos.is_empty() {
+        if !(state.skip_transposed && state.solutions.iter().any(|square| is_transposed(square, board))) {
+            state.solutions.push(board.clone());
+        }
+        return state.solutions.len() >= state.max_solutions;
+    }
+    let mut valid = false;
+    let current_word_pos = words_pos.last().unwrap();
+    let current_word_board = board.get_word(current_word_pos);
+    let partner = state.partners.get(current_word_pos).copied();
+    let partner_board = partner.map(|p| board.get_word(&p));
+
+    // get valid words from cache if possible otherwise update cache
+    let valid_words = state.words_map_cache.entry(current_word_board.clone()).or_insert_with(|| {
+        get_valid_words(words_len.get(&current_word_board.chars().count()).unwrap_or(&EMPTY_VEC), current_word_board.as_str())
+    }).clone();
+
+    // loop thorugh all valid words
+    for current_word in valid_words {
+        // check if the word has been used
+        let uses = state.words_used.get(current_word).copied().unwrap_or(0);
+        if !state.rep_words && uses >= state.max_uses.get(current_word).copied().unwrap_or(1) {
+            continue;
+        }
+
+        // set word in the board
+        board.set_word(current_word_pos, current_word);
+        if let Some(partner) = &partner {
+            board.set_word(partner, current_word);
+        }
+
+        // progress
+        state.visited_nodes += 1;
+        if state.visited_nodes.is_multiple_of(PROGRESS_CHECK_NODES) && state.last_progress.elapsed() >= state.progress_interval {
+            state.publish(board, state.words - words_pos.len() + 1);
+            if state.stopped {
+                break;
+            }
+        }
+
+        // check that exists at least one intersecting word for each letter of the current word
+        let mut sol = true;
+        let partner_intersect = partner.iter().flat_map(|p| words_intersect[p].iter());
+        for word_pos_intersect in words_intersect[current_word_pos].iter().chain(partner_intersect) {
+            let word_board_intersect = board.get_word(word_pos_intersect);
+            let words_intersect_num: usize;
+
+            // get valid words from cache if possible otherwise create new vec and update cache
+            if let Some(valid_words_cached) = state.words_map_cache.get(&word_board_intersect) {
+                words_intersect_num = valid_words_cached.len();
+            }
+            else {
+                let valid_words_intersect = get_valid_words(words_len.get(&word_board_intersect.chars().count()).unwrap_or(&EMPTY_VEC), word_board_intersect.as_str());
+                words_intersect_num = valid_words_intersect.len();
+
+                state.words_map_cache.insert(word_board_intersect, valid_words_intersect);
+            }
+
+            // stop if there are no valid words
+            if words_intersect_num == 0 {
+                sol = false;
+                break;  
+            }
+        }
+        
+        // continue recursively if there are intersecting words for each letter of the current word
+        if sol {
+            if !state.rep_words {
+                state.words_used.insert(current_word, uses + 1);
+            }
+
+            valid = fill_board(board, words_len, &words_pos[..words_pos.len() - 1], words_intersect, state);
+            if valid || state.stopped {
+                break;
+            }
+            if !state.rep_words {
+                state.words_used.insert(current_word, uses);
+            }
+        }
+    }
+
+    if !valid {
+        board.set_word(current_word_pos, current_word_board.as_str());
+        if let (Some(partner), Some(partner_board)) = (&partner, &partner_board) {
+            board.set_word(partner, partner_board);
+        }
+    }
+
+    valid
+}
+
+
+/// Get the words matching the pattern of a word on the board, where ' ' matches any letter
+pub fn get_valid_words<'a>(words: &'a [&str], word_board: &str) -> Vec<&'a str> {
+    words
+        .iter()
+        .filter(|&&word| is_valid(word_board, word))
+        .cloned()
+        .collect()
+}
+
+
+/// Word matching a slot with the number of words left to each of its crossing slots once placed
+#[derive(Debug, Clone)]
+pub struct Candidate<'a> {
+    pub word: &'a str,
+    /// Words left to the crossing slots, in the order of the letters of the slot
+    pub crossings: Vec<usize>,
+}
+
+
+impl Candidate<'_> {
+    /// Words left to the most constrained crossing slot, 0 when the word leads to a dead end.
+    /// None if the slot has no crossing slots
+    pub fn score(&self) -> Option<usize> {
+        self.crossings.iter().copied().min()
+    }
+}
+
+
+/// Get the words matching the current pattern of a slot, counting for each of them
+/// the words that would be left to the crossing slots
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use crosswords_generator::{get_candidates, Board, Dictionary, Dir, WordPos};
+///
+/// let words: BTreeMap<String, Vec<String>> = ["AB", "AC", "BD", "CD"].iter()
+///     .map(|word| (word.to_string(), Vec::new()))
+///     .collect();
+/// let dictionary = Dictionary::new(words);
+///
+/// let board = Board::new(2, 2);
+/// let candidates = get_candidates(&board, &WordPos::new(0, 0, Dir::HOR, 2), &dictionary.index());
+/// let bd = candidates.iter().find(|c| c.word == "BD").unwrap();
+/// assert_eq!(bd.crossings, vec![1, 0]);
+/// assert_eq!(bd.score(), Some(0));
+/// ```
+pub fn get_candidates<'a>(board: &Board, word_pos: &WordPos, words_len: &WordIndex<'a>) -> Vec<Candidate<'a>> {
+    let pattern = board.get_word(word_pos);
+    let words = words_len.get(&pattern.chars().count()).unwrap_or(&EMPTY_VEC);
+
+    // for each cell of the slot, words of the crossing slot grouped by the letter in the crossing cell,
+    // the first letter for rebus cells
+    let crossings: Vec<Option<HashMap<char, usize>>> = (0..word_pos.len)
+        .map(|k| {
+            let (x, y) = word_pos.cell(k);
+            let crossing = board.word_pos_at(x, y, word_pos.dir.other())?;
+            let index = board.char_index(&crossing, crossing.index_of(x, y)?);
+            let crossing_pattern = board.get_word(&crossing);
+            let mut counts: HashMap<char, usize> = HashMap::new();
+            for word in words_len.get(&crossing_pattern.chars().count()).unwrap_or(&EMPTY_VEC) {
+                if is_valid(&crossing_pattern, word) {
+                    if let Some(c) = word.chars().nth(index) {
+                        *counts.entry(c).or_default() += 1;
+                    }
+                }
+            }
+            Some(counts)
+        })
+        .collect();
+
+    words.iter()
+        .filter(|word| is_valid(&pattern, word))
+        .map(|&word| Candidate {
+            word,
+            crossings: (0..word_pos.len).zip(&crossings)
+                .filter_map(|(k, counts)| {
+                    let c = word.chars().nth(board.char_index(word_pos, k))?;
+                    counts.as_ref().map(|counts| counts.get(&c).copied().unwrap_or(0))
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+
+/// Count the words matching the current pattern of every slot of the board
+pub fn count_candidates(board: &Board, words_len: &WordIndex) -> Vec<(WordPos, usize)> {
+    let mut cache: HashMap<String, usize> = HashMap::new();
+    board.get_words_pos().into_iter()
+        .map(|word_pos| {
+            let pattern = board.get_word(&word_pos);
+            let count = *cache.entry(pattern).or_insert_with_key(|pattern| {
+                words_len.get(&pattern.chars().count()).map_or(0, |words| words.iter().filter(|word| is_valid(pattern, word)).count())
+            });
+            (word_pos, count)
+        })
+        .collect()
+}
+
+
+/// Check if the word matches the pattern of a word on the board
+pub fn is_valid(word_board: &str, word: &str) -> bool {
+    let mut word_chars = word.chars();
+    for c in word_board.chars() {
+        let c2 = word_chars.next().unwrap();
+        if c == ' ' {
+            continue;
+        }
+        if c != c2 {
+            return false;
+        }
+    }
+    true
+}
+
+
+/// Keep the previous definitions of the words that did not change between `old` and `board`
+pub fn keep_definitions(old: &Board, old_definitions: &[(WordPos, String)], board: &Board,
+                        definitions: &mut [(WordPos, String)]) {
+    for (word_pos, def) in definitions.iter_mut() {
+        if old.get_word(word_pos) != board.get_word(word_pos) {
+            continue;
+        }
+        if let Some((_, old_def)) = old_definitions.iter().find(|(wp, _)| wp == word_pos) {
+            def.clone_from(old_def);
+        }
+    }
+}
+
+
+/// Pick a random definition for every word of a filled board
+pub fn get_definitions(board: &Board, dictionary: &Dictionary) -> Result<Vec<(WordPos, String)>, Error> {
+    let mut list_defs: Vec<(WordPos, String)> = Vec::new();
+    let words_pos = board.get_words_pos();
+
+    for word_pos in words_pos {
+        let word = board.get_word(&word_pos);
+        let def_string = dictionary.definitions(&word)
+            .and_then(|defs| defs.choose(&mut rand::thread_rng()))
+            .ok_or(Error::MissingDefinitions(word))?
+            .clone();
+
+        list_defs.push((word_pos, def_string));
+    }
+
+    Ok(list_defs)
+}
+
+/// Same as [`get_definitions`], choosing the shortest definition of each word
+/// so that it fits in the clue cells of an arrowword
+pub fn get_short_definitions(board: &Board, dictionary: &Dictionary) -> Result<Vec<(WordPos, String)>, Error> {
+    board.get_words_pos().into_iter()
+        .map(|word_pos| {
+            let word = board.get_word(&word_pos);
+            let def_string = dictionary.definitions(&word)
+                .and_then(|defs| defs.iter().min_by_key(|def| def.chars().count()))
+                .ok_or(Error::MissingDefinitions(word))?
+                .clone();
+            Ok((word_pos, def_string))
+        })
+        .collect()
+}
@@ -1,50 +1,1201 @@
-use std::{collections::HashMap, ops::RangeInclusive};
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use eframe::egui::{self, Frame, Label, RichText, Sense, UiBuilder, Widget};
 use egui::Color32;
 use egui_extras::{Size, StripBuilder};
 use egui_modal::{Icon, Modal};
 
-use crate::{generate, get_definitions, utils::{Board, Dir, Solution, WordPos}};
+use rand::Rng;
+use rand::seq::SliceRandom;
 
+use crosswords_generator::arrowword::{self, ArrowClue};
+use crosswords_generator::mask::Mask;
+use crosswords_generator::utils::{is_block, CLUE_CELL, OUTSIDE_CELL};
+use crosswords_generator::{export, count_candidates, generate_with_progress, get_candidates, get_definitions, get_short_definitions, keep_definitions, regenerate_with_progress, Board, Candidate, Dictionary, Dir, Error, GenerateOptions, GridStats, Play, Progress, Project, Rebus, Solution, Symmetry, WordIndex, WordPos};
 
-pub struct BaseApp<'a> {
-    words_len: HashMap<usize, Vec<&'a str>>,
-    words_def: &'a serde_json::Value,
+
+const LOCKED_COLOR: Color32 = Color32::from_rgb(0, 0, 160);
+const SELECTED_COLOR: Color32 = Color32::from_rgb(255, 230, 120);
+const WORD_COLOR: Color32 = Color32::from_rgb(200, 225, 255);
+const DEAD_COLOR: Color32 = Color32::from_rgb(255, 160, 160);
+const LOW_COLOR: Color32 = Color32::from_rgb(255, 205, 140);
+const REGION_COLOR: Color32 = Color32::from_rgb(215, 195, 255);
+const WRONG_COLOR: Color32 = Color32::from_rgb(200, 0, 0);
+const REVEALED_COLOR: Color32 = Color32::from_rgb(0, 130, 0);
+const CLUE_CELL_COLOR: Color32 = Color32::from_rgb(210, 210, 210);
+const HISTORY_SIZE: usize = 100;
+const BAR_WIDTH: f32 = 3.0;
+// Slots with fewer candidates are highlighted
+const LOW_CANDIDATES: usize = 10;
+
+
+// What a click on a cell does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EditMode {
+    // select the cell to type letters
+    Letters,
+    // switch the cell between black and white
+    BlackSquares,
+    // switch the cell between clue cell (arrowwords) and white
+    ClueCells,
+    // select a rectangular region to regenerate
+    Region,
+    // switch the bar on the nearest edge of the cell
+    Bars,
+    // switch the cell between outside the board shape and white
+    Shape,
+}
+
+
+// State of the grid saved in the undo/redo history, the size is the size of the grid
+#[derive(Debug, Clone)]
+struct Snapshot {
+    grid: Vec<Vec<char>>,
+    locked: Vec<Vec<bool>>,
+    bars: Vec<Vec<[bool; 2]>>,
+    rebus: BTreeMap<(usize, usize), String>,
+    definitions: Vec<(WordPos, String)>,
+}
+
+
+// Words matching the selected slot
+struct Candidates {
+    word_pos: WordPos,
+    pattern: String,
+    list: Vec<Candidate<'static>>,
+}
+
+
+// Order of the candidates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CandidateSort {
+    // most words left to the crossing slots first
+    Score,
+    Alphabetical,
+}
+
+
+// Cells affected by check and reveal in play mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PlayScope {
+    Cell,
+    Word,
+    Puzzle,
+}
+
+
+// Filled board, outcome of the search and definitions
+type SearchResult = Result<(Board, Solution, Vec<(WordPos, String)>), Error>;
+
+
+// Messages sent by the worker thread
+enum SearchMessage {
+    Progress(Progress),
+    Done(SearchResult),
+}
+
+
+// Search running in the worker thread with its latest snapshot
+struct Search {
+    receiver: mpsc::Receiver<SearchMessage>,
+    stop: Arc<AtomicBool>,
+    progress: Option<Progress>,
+    // board and definitions before regenerating a part of the board
+    previous: Option<(Board, Vec<(WordPos, String)>)>,
+}
+
+
+pub struct BaseApp {
+    words_len: WordIndex<'static>,
+    words_def: &'static Dictionary,
+    dictionary: String,
+    // translations of a bilingual crossword and if they are reversed
+    translations: Option<(String, bool)>,
 
     width: usize,
     height: usize,
     grid: Vec<Vec<char>>,
+    locked: Vec<Vec<bool>>,
+    // bars on the right and bottom edge of each cell, indexed by Dir
+    bars: Vec<Vec<[bool; 2]>>,
+    // letters of the rebus cells
+    rebus: BTreeMap<(usize, usize), String>,
+    // rebus of the selected cell being edited in the toolbar
+    rebus_edit: String,
+    rebus_cell: Option<(usize, usize)>,
     shuffle: bool,
     rep_words: bool,
+    fixed_seed: bool,
+    seed: u64,
     modal: Modal,
+    dialog: Modal,
     result: Option<Solution>,
     definitions: Vec<(WordPos, String)>,
+    project_path: Option<String>,
+    project_path_edit: String,
+    mask_path_edit: String,
+    search: Option<Search>,
+    edit_mode: EditMode,
+    symmetry: Symmetry,
+    selected: Option<(usize, usize)>,
+    direction: Dir,
+    undo_stack: Vec<Snapshot>,
+    redo_stack: Vec<Snapshot>,
+    resizing: bool,
+    candidates: Option<Candidates>,
+    candidate_sort: CandidateSort,
+    // candidates of the horizontal and vertical slot of each cell, computed for slot_counts_board
+    slot_counts: Vec<Vec<[Option<usize>; 2]>>,
+    slot_counts_board: Option<Board>,
+    show_clues: bool,
+    clues_left: bool,
+    show_stats: bool,
+    // selection shown in the clue panel, to scroll to the clue when it changes
+    clues_selection: (Option<(usize, usize)>, Dir),
+    // opposite corners of the region to regenerate
+    region: Option<((usize, usize), (usize, usize))>,
+    play: Option<Play>,
+    play_start: Instant,
+    // time to solve the puzzle, once solved
+    play_time: Option<Duration>,
 }
 
 
-impl<'a> BaseApp<'a> {
-    pub fn new(ctx: &egui::Context, words_len: HashMap<usize, Vec<&'a str>>, words_def: &'a serde_json::Value) -> Self {
+// Edge of the cell nearest to the point, as taken by toggle_bar:
+// HOR for the left and right edges, `true` for the left and top ones
+fn nearest_edge(rect: egui::Rect, pos: egui::Pos2) -> (Dir, bool) {
+    [
+        (pos.x - rect.left(), (Dir::HOR, true)),
+        (rect.right() - pos.x, (Dir::HOR, false)),
+        (pos.y - rect.top(), (Dir::VER, true)),
+        (rect.bottom() - pos.y, (Dir::VER, false)),
+    ].into_iter()
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map_or((Dir::HOR, false), |(_, edge)| edge)
+}
+
+
+impl BaseApp {
+    pub fn new(ctx: &egui::Context, words_len: WordIndex<'static>, words_def: &'static Dictionary,
+                dictionary: &str, translations: Option<(String, bool)>, project: Option<(String, Project)>) -> Self {
         let modal = Modal::new(ctx, "modal_result");
-        Self {
+        let dialog = Modal::new(ctx, "modal_dialog");
+        let mut app = Self {
             words_len,
             words_def,
+            dictionary: dictionary.to_owned(),
+            translations,
 
             width: 5,
             height: 5,
             grid: vec![vec![' '; 5]; 5],
+            locked: vec![vec![false; 5]; 5],
+            bars: vec![vec![[false; 2]; 5]; 5],
+            rebus: BTreeMap::new(),
+            rebus_edit: String::new(),
+            rebus_cell: None,
             shuffle: false,
             rep_words: false,
+            fixed_seed: false,
+            seed: 0,
             modal,
+            dialog,
             result: None,
             definitions: Vec::new(),
+            project_path: None,
+            project_path_edit: "project.json".to_owned(),
+            mask_path_edit: "mask.txt".to_owned(),
+            search: None,
+            edit_mode: EditMode::Letters,
+            symmetry: Symmetry::None,
+            selected: None,
+            direction: Dir::HOR,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            resizing: false,
+            candidates: None,
+            candidate_sort: CandidateSort::Score,
+            slot_counts: Vec::new(),
+            slot_counts_board: None,
+            show_clues: true,
+            clues_left: true,
+            show_stats: false,
+            clues_selection: (None, Dir::HOR),
+            region: None,
+            play: None,
+            play_start: Instant::now(),
+            play_time: None,
+        };
+        if let Some((path, project)) = project {
+            match app.load_project(project) {
+                Ok(()) => app.project_path = Some(path.clone()),
+                Err(e) => app.show_error(e),
+            }
+            app.project_path_edit = path;
+        }
+        app
+    }
+
+    // Keep grid and locked cells in sync with width and height
+    fn resize_grid(&mut self) {
+        self.grid.resize(self.height, vec![' '; self.width]);
+        self.locked.resize(self.height, vec![false; self.width]);
+        self.bars.resize(self.height, vec![[false; 2]; self.width]);
+        for v in self.grid.iter_mut() {
+            v.resize(self.width, ' ');
+        }
+        for v in self.locked.iter_mut() {
+            v.resize(self.width, false);
+        }
+        for v in self.bars.iter_mut() {
+            v.resize(self.width, [false; 2]);
+        }
+        let (width, height) = (self.width, self.height);
+        self.rebus.retain(|&(x, y), _| x < width && y < height);
+        if self.selected.is_some_and(|(x, y)| x >= self.width || y >= self.height) {
+            self.selected = None;
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            grid: self.grid.clone(),
+            locked: self.locked.clone(),
+            bars: self.bars.clone(),
+            rebus: self.rebus.clone(),
+            definitions: self.definitions.clone(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.height = snapshot.grid.len();
+        self.width = snapshot.grid.first().map_or(0, Vec::len);
+        self.grid = snapshot.grid;
+        self.locked = snapshot.locked;
+        self.bars = snapshot.bars;
+        self.rebus = snapshot.rebus;
+        self.definitions = snapshot.definitions;
+        self.resize_grid();
+    }
+
+    // Add a state to the undo history, the redo history is lost
+    fn push_history(&mut self, snapshot: Snapshot) {
+        if self.undo_stack.len() == HISTORY_SIZE {
+            self.undo_stack.remove(0);
+        }
+        self.undo_stack.push(snapshot);
+        self.redo_stack.clear();
+    }
+
+    // Save the current state before changing the grid
+    fn checkpoint(&mut self) {
+        self.push_history(self.snapshot());
+    }
+
+    fn undo(&mut self) {
+        if let Some(snapshot) = self.undo_stack.pop() {
+            self.redo_stack.push(self.snapshot());
+            self.restore(snapshot);
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(snapshot) = self.redo_stack.pop() {
+            self.undo_stack.push(self.snapshot());
+            self.restore(snapshot);
+        }
+    }
+
+    // Switch a cell between the given block ('#', clue cell or outside cell) and white together with its symmetric cells,
+    // the letters in the changed cells are removed
+    fn toggle_block(&mut self, x: usize, y: usize, block: char) {
+        if self.play.is_some() {
+            return;
+        }
+        self.checkpoint();
+        let c = if self.grid[y][x] == block {' '} else {block};
+        for (i, j) in self.symmetry.partners(x, y, self.width, self.height) {
+            self.grid[j][i] = c;
+            self.locked[j][i] = false;
+            self.rebus.remove(&(i, j));
+        }
+        self.definitions.clear();
+    }
+
+    // Check if the black cells of the grid follow the selected symmetry
+    fn is_symmetric(&self) -> bool {
+        self.board().is_symmetric(self.symmetry)
+    }
+
+    // Board with the content of the grid
+    fn board(&self) -> Board {
+        let rows: Vec<String> = self.grid.iter().map(|v| v.iter().collect()).collect();
+        let mut board = Board::from_rows(&rows);
+        for (j, v) in self.bars.iter().enumerate() {
+            for (i, bar) in v.iter().enumerate() {
+                let _ = board.set_bar(i, j, Dir::HOR, bar[Dir::HOR as usize]);
+                let _ = board.set_bar(i, j, Dir::VER, bar[Dir::VER as usize]);
+            }
+        }
+        for (&(x, y), letters) in &self.rebus {
+            if board.valid(x, y) {
+                let _ = board.set_rebus(x, y, letters);
+            }
+        }
+        board
+    }
+
+    // Make the cell a rebus cell with the given letters, no letters make it a normal cell
+    fn set_rebus(&mut self, x: usize, y: usize, letters: &str) {
+        let letters = letters.trim().to_uppercase();
+        if self.rebus.get(&(x, y)).map_or("", String::as_str) == letters || is_block(self.grid[y][x]) {
+            return;
+        }
+        self.checkpoint();
+        match letters.chars().next() {
+            Some(first) => {
+                self.grid[y][x] = first;
+                self.locked[y][x] = true;
+                self.rebus.insert((x, y), letters);
+            },
+            None => {
+                self.rebus.remove(&(x, y));
+            },
+        }
+        self.definitions.clear();
+    }
+
+    // Give the grid the shape of the mask, a text mask also sets the size of the grid
+    fn apply_mask(&mut self, mask: Mask) {
+        self.checkpoint();
+        self.width = mask.width();
+        self.height = mask.height();
+        self.resize_grid();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if !mask.is_inside(x, y) {
+                    self.grid[y][x] = OUTSIDE_CELL;
+                    self.locked[y][x] = false;
+                    self.rebus.remove(&(x, y));
+                }
+                else if self.grid[y][x] == OUTSIDE_CELL {
+                    self.grid[y][x] = ' ';
+                }
+            }
+        }
+        self.definitions.clear();
+    }
+
+    // Switch the bar on an edge of a cell: `dir` is HOR for the left and right edges,
+    // VER for the top and bottom ones. The bars of the left and top edges belong to the neighbour
+    fn toggle_bar(&mut self, x: usize, y: usize, dir: Dir, before: bool) {
+        let (x, y) = match (dir, before) {
+            (Dir::HOR, true) if x > 0 => (x - 1, y),
+            (Dir::VER, true) if y > 0 => (x, y - 1),
+            (_, true) => return,
+            (_, false) => (x, y),
+        };
+        // no bars on the border of the grid
+        if (dir == Dir::HOR && x + 1 >= self.width) || (dir == Dir::VER && y + 1 >= self.height) {
+            return;
+        }
+        self.checkpoint();
+        self.bars[y][x][dir as usize] ^= true;
+        self.definitions.clear();
+    }
+
+    // Write a letter in a cell, typed letters are locked so that the solver keeps them
+    fn set_letter(&mut self, x: usize, y: usize, c: char) {
+        if let Some(play) = &mut self.play {
+            let _ = play.set(x, y, c);
+            if play.is_solved() && self.play_time.is_none() {
+                self.play_time = Some(self.play_start.elapsed());
+            }
+            return;
+        }
+        self.checkpoint();
+        self.grid[y][x] = c;
+        self.locked[y][x] = c != ' ';
+        self.rebus.remove(&(x, y));
+        self.definitions.clear();
+    }
+
+    // Letter shown in the cell: the grid while editing, the letters of the player in play mode
+    fn cell(&self, x: usize, y: usize) -> char {
+        match &self.play {
+            Some(play) => play.get(x, y).unwrap_or(' '),
+            None => self.grid[y][x],
+        }
+    }
+
+    // Start solving the filled grid, or go back to editing
+    fn toggle_play(&mut self) {
+        if self.play.is_some() {
+            self.play = None;
+        }
+        else {
+            self.play = Some(Play::new(self.board()));
+            self.play_start = Instant::now();
+            self.play_time = None;
+        }
+    }
+
+    // Grid can be solved only when every open cell has a letter
+    fn can_play(&self) -> bool {
+        self.grid.iter().flatten().all(|&c| c != ' ')
+    }
+
+    // Check or reveal the selected cell, the selected word or the whole puzzle
+    fn play_action(&mut self, reveal: bool, scope: PlayScope) {
+        let cells = match scope {
+            PlayScope::Cell => self.selected.into_iter().collect(),
+            PlayScope::Word => self.selected_word(),
+            PlayScope::Puzzle => self.play.as_ref().map(Play::cells).unwrap_or_default(),
+        };
+        let Some(play) = &mut self.play else {
+            return;
+        };
+        if reveal {
+            let _ = play.reveal(&cells);
+        }
+        else {
+            let _ = play.check(&cells);
+        }
+        if play.is_solved() && self.play_time.is_none() {
+            self.play_time = Some(self.play_start.elapsed());
+        }
+    }
+
+    // Active clue, check and reveal buttons and timer of the play mode
+    fn show_play_bar(&mut self, ui: &mut egui::Ui) {
+        let board = self.board();
+        let active = self.selected.and_then(|(x, y)| board.word_pos_at(x, y, self.direction));
+        let clue = active.map(|word_pos| {
+            let number = board.get_numbered_words_pos().into_iter()
+                .find(|(_, wp)| *wp == word_pos)
+                .map_or(0, |(number, _)| number);
+            let text = self.definitions.iter().find(|(wp, _)| *wp == word_pos).map_or("", |(_, def)| def.as_str());
+            format!("{} {}: {}", number, if word_pos.dir == Dir::HOR {"Across"} else {"Down"}, text)
+        });
+        ui.label(RichText::new(clue.unwrap_or_else(|| "Select a cell to see its clue".to_owned())).strong());
+
+        let mut action = None;
+        ui.horizontal(|ui| {
+            for (reveal, name) in [(false, "Check"), (true, "Reveal")] {
+                ui.menu_button(name, |ui| {
+                    for (scope, scope_name) in [(PlayScope::Cell, "Cell"), (PlayScope::Word, "Word"), (PlayScope::Puzzle, "Puzzle")] {
+                        if ui.button(scope_name).clicked() {
+                            action = Some((reveal, scope));
+                            ui.close_menu();
+                        }
+                    }
+                });
+            }
+            ui.separator();
+
+            let elapsed = self.play_time.unwrap_or_else(|| self.play_start.elapsed()).as_secs();
+            ui.label(format!("Time: {:02}:{:02}", elapsed / 60, elapsed % 60));
+            if self.play_time.is_some() {
+                ui.colored_label(REVEALED_COLOR, "Solved!");
+            }
+            else {
+                ui.ctx().request_repaint_after(Duration::from_secs(1));
+            }
+        });
+
+        if let Some((reveal, scope)) = action {
+            self.play_action(reveal, scope);
+        }
+    }
+
+    // Cell next to (x, y) in the current direction, going backwards if `back`
+    fn next_cell(&self, x: usize, y: usize, back: bool) -> Option<(usize, usize)> {
+        let (dx, dy) = if self.direction == Dir::HOR {(1, 0)} else {(0, 1)};
+        let (x, y) = if back {
+            (x.checked_sub(dx)?, y.checked_sub(dy)?)
+        }
+        else {
+            (x + dx, y + dy)
+        };
+        (x < self.width && y < self.height).then_some((x, y))
+    }
+
+    // Cells of the word crossing the selected cell in the current direction
+    fn selected_word(&self) -> Vec<(usize, usize)> {
+        self.selected
+            .and_then(|(x, y)| self.board().word_pos_at(x, y, self.direction))
+            .map_or_else(Vec::new, |word_pos| (0..word_pos.len).map(|k| word_pos.cell(k)).collect())
+    }
+
+    // Write a word in its slot, the letters are locked. The rebus cells keep their letters
+    fn place_word(&mut self, word_pos: &WordPos, word: &str) {
+        self.checkpoint();
+        let mut board = self.board();
+        board.set_word(word_pos, word);
+        for k in 0..word_pos.len {
+            let (x, y) = word_pos.cell(k);
+            if let Ok(c) = board.get(x, y) {
+                self.grid[y][x] = c;
+                self.locked[y][x] = true;
+            }
+        }
+        self.definitions.clear();
+    }
+
+    // Keep the candidates in sync with the selected slot and its pattern
+    fn update_candidates(&mut self) {
+        let selected = self.selected.filter(|_| self.edit_mode == EditMode::Letters && self.search.is_none() && self.play.is_none());
+        let Some((x, y)) = selected else {
+            self.candidates = None;
+            return;
+        };
+        let board = self.board();
+        let Some(word_pos) = board.word_pos_at(x, y, self.direction) else {
+            self.candidates = None;
+            return;
+        };
+
+        let pattern = board.get_word(&word_pos);
+        if self.candidates.as_ref().is_some_and(|c| c.word_pos == word_pos && c.pattern == pattern) {
+            return;
+        }
+        let list = get_candidates(&board, &word_pos, &self.words_len);
+        self.candidates = Some(Candidates { word_pos, pattern, list });
+        self.sort_candidates();
+    }
+
+    fn sort_candidates(&mut self) {
+        let Some(candidates) = &mut self.candidates else {
+            return;
+        };
+        match self.candidate_sort {
+            CandidateSort::Score => candidates.list.sort_by(|a, b| b.score().cmp(&a.score())
+                .then_with(|| b.crossings.iter().sum::<usize>().cmp(&a.crossings.iter().sum()))
+                .then_with(|| a.word.cmp(b.word))),
+            CandidateSort::Alphabetical => candidates.list.sort_by(|a, b| a.word.cmp(b.word)),
+        }
+    }
+
+    // Count the candidates of every slot when the board changes: letters, bars or rebus cells
+    fn update_slot_counts(&mut self) {
+        if self.search.is_some() || self.play.is_some() {
+            return;
+        }
+        let board = self.board();
+        if self.slot_counts_board.as_ref() == Some(&board) {
+            return;
+        }
+        self.slot_counts = vec![vec![[None; 2]; self.width]; self.height];
+        for (word_pos, count) in count_candidates(&board, &self.words_len) {
+            for k in 0..word_pos.len {
+                let (x, y) = word_pos.cell(k);
+                self.slot_counts[y][x][word_pos.dir as usize] = Some(count);
+            }
+        }
+        self.slot_counts_board = Some(board);
+    }
+
+    // Replace a clue with another definition of its word
+    fn reroll_clue(&mut self, k: usize) {
+        let (word_pos, current) = &self.definitions[k];
+        let word = self.board().get_word(word_pos);
+        let others: Vec<&String> = self.words_def.definitions(&word).unwrap_or_default().iter()
+            .filter(|def| *def != current)
+            .collect();
+        if let Some(def) = others.choose(&mut rand::thread_rng()).map(|def| (*def).clone()) {
+            self.checkpoint();
+            self.definitions[k].1 = def;
+        }
+    }
+
+    // Side panel with the numbered clues, the clues of the selected cell are highlighted
+    fn show_clues(&mut self, ctx: &egui::Context) {
+        if !self.show_clues {
+            return;
+        }
+        let board = self.board();
+        let numbered = board.get_numbered_words_pos();
+        let active = self.selected.and_then(|(x, y)| board.word_pos_at(x, y, self.direction));
+        let crossing = self.selected.and_then(|(x, y)| board.word_pos_at(x, y, self.direction.other()));
+        let playing = self.play.is_some();
+        let selected_changed = self.clues_selection != (self.selected, self.direction);
+        self.clues_selection = (self.selected, self.direction);
+
+        let mut select = None;
+        let mut reroll = None;
+        let panel = if self.clues_left {egui::SidePanel::left("clues")} else {egui::SidePanel::right("clues")};
+        panel.default_width(260.0).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading("Clues");
+                if ui.small_button(if self.clues_left {"Dock Right"} else {"Dock Left"}).clicked() {
+                    self.clues_left = !self.clues_left;
+                }
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical().auto_shrink([false; 2]).show(ui, |ui| {
+                for (title, dir) in [("Across", Dir::HOR), ("Down", Dir::VER)] {
+                    ui.strong(title);
+                    for (number, word_pos) in numbered.iter().filter(|(_, wp)| wp.dir == dir) {
+                        ui.horizontal(|ui| {
+                            // number, click to select the word on the grid
+                            let mut text = RichText::new(format!("{:>3}", number)).monospace();
+                            if Some(*word_pos) == crossing {
+                                text = text.color(LOCKED_COLOR);
+                            }
+                            let response = ui.selectable_label(Some(*word_pos) == active, text);
+                            if response.clicked() {
+                                select = Some(*word_pos);
+                            }
+                            if Some(*word_pos) == active && selected_changed {
+                                response.scroll_to_me(None);
+                            }
+
+                            // clue, editable, or the pattern of the word if it has no clue yet.
+                            // In play mode the clues are read only and the answers are hidden
+                            match self.definitions.iter().position(|(wp, _)| wp == word_pos) {
+                                Some(k) if playing => {
+                                    ui.label(&self.definitions[k].1);
+                                },
+                                None if playing => (),
+                                Some(k) => {
+                                    let word = board.get_word(word_pos);
+                                    let alternatives = self.words_def.definitions(&word).map_or(0, <[String]>::len) > 1;
+                                    if ui.add_enabled(alternatives, egui::Button::new("🎲").small())
+                                        .on_hover_text("Another definition of the word").clicked() {
+                                        reroll = Some(k);
+                                    }
+                                    // one undo step for each edit of the clue, none if the text is the same
+                                    let response = ui.add(egui::TextEdit::singleline(&mut self.definitions[k].1)
+                                        .desired_width(f32::INFINITY));
+                                    if response.gained_focus() {
+                                        self.checkpoint();
+                                    }
+                                    if response.lost_focus() && self.undo_stack.last().is_some_and(|s| s.definitions == self.definitions) {
+                                        self.undo_stack.pop();
+                                    }
+                                },
+                                None => {
+                                    let pattern: String = board.get_word(word_pos).chars()
+                                        .map(|c| if c == ' ' {'_'} else {c})
+                                        .collect();
+                                    ui.weak(pattern);
+                                },
+                            }
+                        });
+                    }
+                    ui.add_space(8.0);
+                }
+            });
+        });
+
+        if let Some(word_pos) = select {
+            self.selected = Some((word_pos.x, word_pos.y));
+            self.direction = word_pos.dir;
+        }
+        if let Some(k) = reroll {
+            self.reroll_clue(k);
+        }
+    }
+
+    // Window with the statistics of the grid. While playing they are computed from the entries
+    // of the player, the solution would give away the answers
+    fn show_stats(&mut self, ctx: &egui::Context) {
+        let board = self.play.as_ref().map_or_else(|| self.board(), |play| play.entries().clone());
+        let stats = GridStats::new(&board);
+        egui::Window::new("Statistics").open(&mut self.show_stats).resizable(false).show(ctx, |ui| {
+            egui::Grid::new("GridStats").num_columns(2).striped(true).show(ui, |ui| {
+                let lengths: Vec<String> = stats.lengths.iter().map(|(len, n)| format!("{}: {}", len, n)).collect();
+                let letters: Vec<String> = stats.letters.iter().map(|(c, n)| format!("{}: {}", c, n)).collect();
+                let rows = [
+                    ("Words", stats.words.to_string()),
+                    ("Average length", format!("{:.2}", stats.average_length)),
+                    ("Lengths", lengths.join(", ")),
+                    ("Black cells", format!("{} ({:.1}%)", stats.black_cells, stats.black_percentage)),
+                    ("Unchecked cells", stats.unchecked_cells.to_string()),
+                    ("Letters", letters.join(", ")),
+                    ("Scrabble score", stats.scrabble_score.to_string()),
+                    ("Repeated words", stats.repeated_words.to_string()),
+                ];
+                for (name, value) in rows {
+                    ui.label(name);
+                    ui.add(Label::new(value).wrap());
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
+    // Side panel with the words matching the selected slot
+    fn show_candidates(&mut self, ctx: &egui::Context) {
+        let Some(candidates) = &self.candidates else {
+            return;
+        };
+
+        let mut place = None;
+        let mut sort = self.candidate_sort;
+        egui::SidePanel::right("candidates").default_width(200.0).show(ctx, |ui| {
+            ui.heading("Candidates");
+            let pattern: String = candidates.pattern.chars().map(|c| if c == ' ' {'_'} else {c}).collect();
+            ui.label(format!("{} at ({}, {}): {}", if candidates.word_pos.dir == Dir::HOR {"Hor"} else {"Ver"},
+                candidates.word_pos.x, candidates.word_pos.y, pattern));
+            ui.horizontal(|ui| {
+                ui.label("Sort:");
+                ui.selectable_value(&mut sort, CandidateSort::Score, "Score");
+                ui.selectable_value(&mut sort, CandidateSort::Alphabetical, "A-Z");
+            });
+            ui.label(format!("{} words, double-click to place", candidates.list.len()));
+            ui.separator();
+
+            let row_height = ui.text_style_height(&egui::TextStyle::Body) + ui.spacing().item_spacing.y;
+            egui::ScrollArea::vertical().auto_shrink([false; 2]).show_rows(ui, row_height, candidates.list.len(), |ui, rows| {
+                for candidate in &candidates.list[rows] {
+                    ui.horizontal(|ui| {
+                        let crossings: Vec<String> = candidate.crossings.iter().map(usize::to_string).collect();
+                        let response = ui.selectable_label(false, RichText::new(candidate.word).monospace())
+                            .on_hover_text(format!("Words left to the crossing slots: {}", crossings.join(", ")));
+                        if response.double_clicked() {
+                            place = Some(candidate.word);
+                        }
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            match candidate.score() {
+                                Some(0) => ui.colored_label(Color32::RED, "0"),
+                                Some(score) => ui.label(score.to_string()),
+                                None => ui.label("-"),
+                            };
+                        });
+                    });
+                }
+            });
+        });
+
+        let word_pos = candidates.word_pos;
+        if sort != self.candidate_sort {
+            self.candidate_sort = sort;
+            self.sort_candidates();
+        }
+        if let Some(word) = place {
+            self.place_word(&word_pos, word);
+        }
+    }
+
+    // Cells inside the region to regenerate
+    fn region_cells(&self) -> Vec<(usize, usize)> {
+        let Some(((x0, y0), (x1, y1))) = self.region else {
+            return Vec::new();
+        };
+        (y0.min(y1)..=y0.max(y1))
+            .flat_map(|y| (x0.min(x1)..=x0.max(x1)).map(move |x| (x, y)))
+            .filter(|&(x, y)| x < self.width && y < self.height)
+            .collect()
+    }
+
+    // Cells regenerated by the toolbar button: the region or the selected word
+    fn regenerate_cells(&self) -> Vec<(usize, usize)> {
+        match self.edit_mode {
+            EditMode::Region => self.region_cells(),
+            _ => self.selected_word(),
+        }
+    }
+
+    // Select a cell or change its state, depending on the edit mode.
+    // With `extend` the region grows up to the cell instead of starting again
+    // In the Bars edit mode `edge` is the edge of the cell nearest to the click, see toggle_bar
+    fn click_cell(&mut self, x: usize, y: usize, extend: bool, edge: Option<(Dir, bool)>) {
+        let edit_mode = if self.play.is_some() {EditMode::Letters} else {self.edit_mode};
+        match edit_mode {
+            EditMode::Letters => {
+                if self.selected == Some((x, y)) {
+                    self.direction = self.direction.other();
+                }
+                self.selected = Some((x, y));
+            },
+            EditMode::BlackSquares => self.toggle_block(x, y, '#'),
+            EditMode::ClueCells => self.toggle_block(x, y, CLUE_CELL),
+            EditMode::Shape => self.toggle_block(x, y, OUTSIDE_CELL),
+            EditMode::Region => {
+                self.region = match self.region {
+                    Some((start, _)) if extend => Some((start, (x, y))),
+                    _ => Some(((x, y), (x, y))),
+                };
+            },
+            EditMode::Bars => {
+                if let Some((dir, before)) = edge {
+                    self.toggle_bar(x, y, dir, before);
+                }
+            },
+        }
+    }
+
+    // Edit the selected cell with the keyboard
+    fn handle_keys(&mut self, ctx: &egui::Context) {
+        use egui::{Event, Key};
+
+        let Some((mut x, mut y)) = self.selected else {
+            return;
+        };
+        let events = ctx.input(|i| i.events.clone());
+        for event in events {
+            match event {
+                Event::Text(text) => {
+                    for c in text.chars() {
+                        match c {
+                            ' ' => self.direction = self.direction.other(),
+                            '.' | '#' => self.toggle_block(x, y, '#'),
+                            CLUE_CELL => self.toggle_block(x, y, CLUE_CELL),
+                            OUTSIDE_CELL => self.toggle_block(x, y, OUTSIDE_CELL),
+                            c if c.is_alphabetic() => {
+                                self.set_letter(x, y, c.to_uppercase().next().unwrap_or(c));
+                                if let Some(next) = self.next_cell(x, y, false).filter(|&(nx, ny)| !is_block(self.grid[ny][nx])) {
+                                    (x, y) = next;
+                                }
+                            },
+                            _ => (),
+                        }
+                    }
+                },
+                Event::Key { key, pressed: true, modifiers, .. } if !modifiers.command => {
+                    match key {
+                        Key::ArrowLeft | Key::ArrowRight | Key::ArrowUp | Key::ArrowDown => {
+                            self.direction = if matches!(key, Key::ArrowLeft | Key::ArrowRight) {Dir::HOR} else {Dir::VER};
+                            let back = matches!(key, Key::ArrowLeft | Key::ArrowUp);
+                            if let Some(next) = self.next_cell(x, y, back) {
+                                (x, y) = next;
+                            }
+                        },
+                        Key::Backspace => {
+                            // clear the cell or, if already empty, the previous one
+                            if self.cell(x, y) == ' ' {
+                                if let Some(prev) = self.next_cell(x, y, true).filter(|&(px, py)| !is_block(self.grid[py][px])) {
+                                    (x, y) = prev;
+                                }
+                            }
+                            if !is_block(self.cell(x, y)) {
+                                self.set_letter(x, y, ' ');
+                            }
+                        },
+                        Key::Delete if !is_block(self.cell(x, y)) => self.set_letter(x, y, ' '),
+                        Key::Escape => {
+                            self.selected = None;
+                            return;
+                        },
+                        _ => (),
+                    }
+                },
+                _ => (),
+            }
+        }
+        self.selected = Some((x, y));
+    }
+
+    // Build a project from the current state
+    fn to_project(&self) -> Project {
+        let mut project = Project::new(self.width, self.height);
+        project.template = self.grid.iter()
+            .map(|v| v.iter().map(|&c| if is_block(c) {c} else {' '}).collect())
+            .collect();
+        project.locked = self.grid.iter().zip(self.locked.iter())
+            .map(|(v, l)| v.iter().zip(l.iter()).map(|(&c, &lock)| if lock {c} else {' '}).collect())
+            .collect();
+        let board = self.board();
+        if board.has_bars() {
+            project.bars = board.bar_rows();
+        }
+        project.rebus = board.rebus_cells().into_iter()
+            .map(|(x, y, text)| Rebus { x, y, text: text.to_owned() })
+            .collect();
+        if !self.definitions.is_empty() {
+            project.set_solution(&board, &self.definitions);
+        }
+        project.seed = self.seed;
+        project.dictionary = self.dictionary.clone();
+        if let Some((path, reverse)) = &self.translations {
+            project.translations = Some(path.clone());
+            project.reverse_translations = *reverse;
+        }
+        project.shuffle = self.shuffle;
+        project.repeat_words = self.rep_words;
+        project
+    }
+
+    // Replace the current state with the given project
+    fn load_project(&mut self, project: Project) -> Result<(), Error> {
+        let board = project.board()?;
+
+        self.width = project.width;
+        self.height = project.height;
+        self.locked = board.rows().iter()
+            .map(|r| r.chars().map(|c| c != ' ' && !is_block(c)).collect())
+            .collect();
+        self.bars = (0..board.height())
+            .map(|j| (0..board.width())
+                .map(|i| [board.has_bar(i, j, Dir::HOR), board.has_bar(i, j, Dir::VER)])
+                .collect())
+            .collect();
+        self.rebus = project.rebus.iter().map(|r| ((r.x, r.y), r.text.clone())).collect();
+        self.grid = project.solution.as_ref().unwrap_or(&board.rows()).iter()
+            .map(|r| r.chars().collect())
+            .collect();
+        self.definitions = project.definitions();
+        self.seed = project.seed;
+        self.fixed_seed = true;
+        self.shuffle = project.shuffle;
+        self.rep_words = project.repeat_words;
+        self.result = None;
+        self.selected = None;
+
+        if project.dictionary != self.dictionary {
+            self.dialog.dialog()
+                .with_title("Different dictionary")
+                .with_body(format!("The project was created with '{}', the loaded dictionary is '{}'.",
+                    project.dictionary, self.dictionary))
+                .with_icon(Icon::Info)
+                .open();
+        }
+        else if project.translations.clone().map(|path| (path, project.reverse_translations)) != self.translations {
+            self.dialog.dialog()
+                .with_title("Different translations")
+                .with_body(format!("The project was created with the translations '{}', the loaded ones are '{}'.",
+                    project.translations.as_deref().unwrap_or("none"),
+                    self.translations.as_ref().map_or("none", |(path, _)| path.as_str())))
+                .with_icon(Icon::Info)
+                .open();
+        }
+        Ok(())
+    }
+
+    fn show_error(&self, error: Error) {
+        self.dialog.dialog()
+            .with_title(error.title())
+            .with_body(error)
+            .with_icon(Icon::Error)
+            .open();
+    }
+
+    fn open_project(&mut self) {
+        let path = self.project_path_edit.clone();
+        let snapshot = self.snapshot();
+        match Project::load(&path).and_then(|project| self.load_project(project)) {
+            Ok(()) => {
+                self.push_history(snapshot);
+                self.project_path = Some(path);
+            },
+            Err(e) => self.show_error(e),
+        }
+    }
+
+    // Write the puzzle next to the project, as an SVG image of the empty grid or as a .puz or ipuz file
+    fn export(&self, extension: &str) {
+        let path = std::path::Path::new(&self.project_path_edit).with_extension(extension);
+        let title = path.file_stem().map_or(String::new(), |stem| stem.to_string_lossy().into_owned());
+        let path = path.to_string_lossy();
+        let board = self.board();
+        let result = match extension {
+            "puz" => export::save_puz(&board, &self.definitions, &title, &path),
+            "ipuz" => export::save_ipuz(&board, &self.definitions, &title, &path),
+            _ => {
+                let clues = arrowword::arrow_clues(&board, &self.definitions).unwrap_or_default();
+                export::save_svg(&board, &clues, false, &path)
+            },
+        };
+        match result {
+            Ok(()) => {
+                self.dialog.dialog()
+                    .with_title("Export")
+                    .with_body(format!("Puzzle saved in '{}'.", path))
+                    .with_icon(Icon::Success)
+                    .open();
+            },
+            Err(e) => self.show_error(e),
+        }
+    }
+
+    fn save_project(&mut self, path: String) {
+        match self.to_project().save(&path) {
+            Ok(()) => {
+                self.project_path_edit = path.clone();
+                self.project_path = Some(path);
+            },
+            Err(e) => self.show_error(e),
+        }
+    }
+
+    // Start filling the grid in the worker thread, keeping black cells and locked letters
+    fn generate(&mut self, ctx: &egui::Context) {
+        self.checkpoint();
+
+        // Clean grid, locked letters are kept
+        for (v, l) in self.grid.iter_mut().zip(self.locked.iter()) {
+            for (cell, lock) in v.iter_mut().zip(l.iter()) {
+                if !is_block(*cell) && !lock {
+                    *cell = ' ';
+                }
+            }
+        }
+
+        // Create board with black cells and locked letters
+        let board = self.board();
+        self.definitions.clear();
+        self.start_search(ctx, board, None);
+    }
+
+    // Fill again the given cells in the worker thread, keeping the rest of the grid
+    fn regenerate(&mut self, ctx: &egui::Context, cells: Vec<(usize, usize)>) {
+        self.checkpoint();
+        let board = self.board();
+        self.start_search(ctx, board, Some(cells));
+    }
+
+    // Run the solver in the worker thread, over the whole board or only over the given cells
+    fn start_search(&mut self, ctx: &egui::Context, mut board: Board, cells: Option<Vec<(usize, usize)>>) {
+        // Pick a new seed
+        if !self.fixed_seed {
+            self.seed = rand::thread_rng().gen();
+        }
+
+        // Process
+        let previous = cells.as_ref().map(|_| (board.clone(), self.definitions.clone()));
+        let options = GenerateOptions::new().shuffle(self.shuffle).seed(self.seed).repeat_words(self.rep_words);
+        let (sender, receiver) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let words_len = self.words_len.clone();
+        let words_def = self.words_def;
+        let thread_stop = Arc::clone(&stop);
+        let ctx = ctx.clone();
+
+        thread::spawn(move || {
+            let report = |progress: &Progress| {
+                let _ = sender.send(SearchMessage::Progress(progress.clone()));
+                ctx.request_repaint();
+                !thread_stop.load(Ordering::Relaxed)
+            };
+            let sol = match &cells {
+                Some(cells) => regenerate_with_progress(&mut board, cells, &words_len, &options, report),
+                None => generate_with_progress(&mut board, &words_len, &options, report),
+            };
+            let result = sol.and_then(|sol| {
+                let definitions = match sol.found {
+                    true if board.has_clue_cells() => get_short_definitions(&board, words_def)?,
+                    true => get_definitions(&board, words_def)?,
+                    false => Vec::new(),
+                };
+                Ok((board, sol, definitions))
+            });
+            let _ = sender.send(SearchMessage::Done(result));
+            ctx.request_repaint();
+        });
+
+        self.search = Some(Search { receiver, stop, progress: None, previous });
+    }
+
+    // Read the messages of the worker thread
+    fn poll_search(&mut self) {
+        let Some(search) = &mut self.search else {
+            return;
+        };
+
+        let mut done = None;
+        while let Ok(message) = search.receiver.try_recv() {
+            match message {
+                SearchMessage::Progress(progress) => search.progress = Some(progress),
+                SearchMessage::Done(result) => done = Some(result),
+            }
+        }
+
+        let Some(done) = done else {
+            return;
+        };
+        let previous = self.search.take().and_then(|search| search.previous);
+        match done {
+            Ok((board, sol, mut definitions)) => {
+                // after a regeneration the words that did not change keep their clues
+                if let Some((old_board, old_definitions)) = previous {
+                    if sol.found {
+                        keep_definitions(&old_board, &old_definitions, &board, &mut definitions);
+                    }
+                    else {
+                        definitions = old_definitions;
+                    }
+                }
+                self.definitions = definitions;
+                self.result = Some(sol);
+                // Update grid with board data
+                self.grid = board.rows().iter().map(|r| r.chars().collect()).collect();
+            },
+            Err(e) => self.show_error(e),
+        }
+    }
+
+    fn stop_search(&self) {
+        if let Some(search) = &self.search {
+            search.stop.store(true, Ordering::Relaxed);
         }
     }
 }
 
 
-impl<'a> eframe::App for BaseApp<'a> {
+impl eframe::App for BaseApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.poll_search();
+        let searching = self.search.is_some();
+        let playing = self.play.is_some();
+
+        // Keyboard editing, unless a text field or a dialog is using the keyboard
+        let keyboard_free = ctx.memory(|m| m.focused().is_none()) && !self.modal.is_open() && !self.dialog.is_open();
+        if !searching && keyboard_free {
+            use egui::{Key, Modifiers};
+            // no history while solving
+            if !playing {
+                if ctx.input_mut(|i| i.consume_key(Modifiers::COMMAND | Modifiers::SHIFT, Key::Z) || i.consume_key(Modifiers::COMMAND, Key::Y)) {
+                    self.redo();
+                }
+                else if ctx.input_mut(|i| i.consume_key(Modifiers::COMMAND, Key::Z)) {
+                    self.undo();
+                }
+            }
+            self.handle_keys(ctx);
+        }
+
+        self.update_slot_counts();
+        self.update_candidates();
+        self.show_clues(ctx);
+        self.show_candidates(ctx);
+        if self.show_stats {
+            self.show_stats(ctx);
+        }
+
+        // Status bar with the latest snapshot of the search
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                match self.search.as_ref().map(|search| &search.progress) {
+                    Some(Some(progress)) => {
+                        ui.spinner();
+                        ui.label(format!("Words: {}/{}", progress.depth, progress.words));
+                        ui.separator();
+                        ui.label(format!("Visited nodes: {}", progress.visited_nodes));
+                        ui.separator();
+                        ui.label(format!("{:.0} nodes/s", progress.nodes_per_sec));
+                        ui.separator();
+                        ui.label(format!("Cache: {}", progress.cache_size));
+                        ui.separator();
+                        ui.label(format!("{:.1} s", progress.time_elapsed as f64 / 1000.0));
+                    },
+                    Some(None) => {
+                        ui.spinner();
+                        ui.label("Searching...");
+                    },
+                    None => {
+                        ui.label("Ready");
+                    },
+                }
+            });
+        });
+
         egui::CentralPanel::default().show(ctx, |ui| {
             let dark_mode = ui.visuals().dark_mode;
             let faded_color = ui.visuals().window_fill();
@@ -63,6 +1214,10 @@ impl<'a> eframe::App for BaseApp<'a> {
                         ui.heading("Solution found!");
                         self.modal.icon(ui, Icon::Success);
                     }
+                    else if sol.cancelled {
+                        ui.heading("Search stopped.");
+                        self.modal.icon(ui, Icon::Info);
+                    }
                     else {
                         ui.heading("Solution not found.");
                         self.modal.icon(ui, Icon::Warning);
@@ -81,13 +1236,15 @@ impl<'a> eframe::App for BaseApp<'a> {
                 }); 
             });
 
-            if !self.result.is_none() {
+            if self.result.is_some() {
                 self.modal.open();
             }
+
+            self.dialog.show_dialog();
             
             StripBuilder::new(ui)
                 .size(Size::exact(64.0))
-                .size(Size::exact(64.0))
+                .size(Size::exact(176.0))
                 .size(Size::remainder())
                 .size(Size::exact(64.0))
                 .vertical(|mut strip| {
@@ -103,6 +1260,9 @@ impl<'a> eframe::App for BaseApp<'a> {
                     });
                     strip.cell(|ui| {
                         ui.separator();
+                        if searching || playing {
+                            ui.disable();
+                        }
                         egui::Grid::new("GridSettings")
                             .num_columns(2)
                             .show(ui, |ui| {
@@ -115,30 +1275,17 @@ impl<'a> eframe::App for BaseApp<'a> {
                                         egui::DragValue::new(&mut self.height)
                                             .range(RangeInclusive::new(2, 100)));
 
-                                    if resp_w.changed() {
-                                        let diff: i32 = self.width as i32 - self.grid.first().unwrap().len() as i32;
-                                        for v in self.grid.iter_mut() {
-                                            for _ in 0..diff.abs() {
-                                                if diff < 0 {
-                                                    v.pop();
-                                                }
-                                                else {
-                                                    v.push(' ');
-                                                }
-                                            }
+                                    // a drag of the size is a single step of the history
+                                    let dragged = resp_w.dragged() || resp_h.dragged();
+                                    if resp_w.changed() || resp_h.changed() {
+                                        if !self.resizing {
+                                            self.checkpoint();
                                         }
+                                        self.resizing = dragged;
+                                        self.resize_grid();
                                     }
-
-                                    if resp_h.changed() {
-                                        let diff: i32 = self.height as i32 - self.grid.len() as i32;
-                                        for _ in 0..diff.abs() {
-                                            if diff < 0 {
-                                                self.grid.pop();
-                                            }
-                                            else {
-                                                self.grid.push(vec![' '; self.width]);
-                                            }
-                                        }
+                                    else if !dragged {
+                                        self.resizing = false;
                                     }
                                 });
                                 ui.end_row();
@@ -154,25 +1301,195 @@ impl<'a> eframe::App for BaseApp<'a> {
                                     ui.add(egui::Checkbox::without_text(&mut self.rep_words));
                                 });
                                 ui.end_row();
+
+                                ui.label("Fixed Seed:");
+                                ui.horizontal(|ui| {
+                                    ui.add(egui::Checkbox::without_text(&mut self.fixed_seed));
+                                    ui.add_enabled(self.fixed_seed, egui::DragValue::new(&mut self.seed));
+                                });
+                                ui.end_row();
+
+                                ui.label("Edit:");
+                                ui.horizontal(|ui| {
+                                    ui.selectable_value(&mut self.edit_mode, EditMode::Letters, "Letters")
+                                        .on_hover_text("Click to select a cell and type letters.\nSpace switches direction, '.' switches black squares.");
+                                    ui.selectable_value(&mut self.edit_mode, EditMode::BlackSquares, "Black Squares")
+                                        .on_hover_text("Click to switch cells between black and white.");
+                                    ui.selectable_value(&mut self.edit_mode, EditMode::ClueCells, "Clue Cells")
+                                        .on_hover_text("Click to switch cells between clue cells (arrowwords) and white.\nIn the Letters mode '@' switches clue cells.");
+                                    ui.selectable_value(&mut self.edit_mode, EditMode::Region, "Region")
+                                        .on_hover_text("Click a corner and Shift+click the opposite one to select a region to regenerate.");
+                                    ui.selectable_value(&mut self.edit_mode, EditMode::Bars, "Bars")
+                                        .on_hover_text("Click near an edge of a cell to switch a thick bar between the cells.");
+                                    ui.selectable_value(&mut self.edit_mode, EditMode::Shape, "Shape")
+                                        .on_hover_text("Click to switch cells between outside the board and white.\nIn the Letters mode '~' switches outside cells.");
+                                });
+                                ui.end_row();
+
+                                ui.label("Shape:");
+                                ui.horizontal(|ui| {
+                                    ui.add(egui::TextEdit::singleline(&mut self.mask_path_edit).desired_width(200.0))
+                                        .on_hover_text("Text file with '.' outside the shape, or PNG image with the shape in dark pixels");
+                                    if ui.button("Load Mask").clicked() {
+                                        match Mask::load(&self.mask_path_edit, self.width, self.height) {
+                                            Ok(mask) => self.apply_mask(mask),
+                                            Err(e) => self.show_error(e),
+                                        }
+                                    }
+                                    if ui.button("Circle").on_hover_text("Fit a circle (an ellipse) in the grid").clicked() {
+                                        self.apply_mask(Mask::ellipse(self.width, self.height));
+                                    }
+                                });
+                                ui.end_row();
+
+                                ui.label("Symmetry:");
+                                ui.horizontal(|ui| {
+                                    egui::ComboBox::from_id_salt("symmetry")
+                                        .selected_text(self.symmetry.name())
+                                        .show_ui(ui, |ui| {
+                                            for symmetry in Symmetry::ALL {
+                                                ui.selectable_value(&mut self.symmetry, symmetry, symmetry.name());
+                                            }
+                                        });
+                                    if self.symmetry != Symmetry::None {
+                                        if self.is_symmetric() {
+                                            ui.colored_label(Color32::DARK_GREEN, "✔ Symmetric");
+                                        }
+                                        else {
+                                            ui.colored_label(Color32::RED, "✖ Not symmetric");
+                                        }
+                                    }
+                                });
+                                ui.end_row();
+
+                                ui.label("Project:");
+                                ui.horizontal(|ui| {
+                                    ui.add(egui::TextEdit::singleline(&mut self.project_path_edit).desired_width(200.0));
+                                    if ui.button("Open").clicked() {
+                                        self.open_project();
+                                    }
+                                    if ui.button("Save").clicked() {
+                                        let path = self.project_path.clone().unwrap_or_else(|| self.project_path_edit.clone());
+                                        self.save_project(path);
+                                    }
+                                    if ui.button("Save As").clicked() {
+                                        self.save_project(self.project_path_edit.clone());
+                                    }
+                                    ui.menu_button("Export", |ui| {
+                                        if ui.button("SVG").on_hover_text("Save the empty grid as an SVG image").clicked() {
+                                            self.export("svg");
+                                            ui.close_menu();
+                                        }
+                                        if ui.button(".puz").on_hover_text("Save the puzzle for Across Lite").clicked() {
+                                            self.export("puz");
+                                            ui.close_menu();
+                                        }
+                                        if ui.button("ipuz").on_hover_text("Save the puzzle in the ipuz format").clicked() {
+                                            self.export("ipuz");
+                                            ui.close_menu();
+                                        }
+                                    });
+                                });
+                                ui.end_row();
                             });
                     });
                     strip.cell(|ui| {
                         ui.separator();
+                        // Toolbar
+                        ui.add_enabled_ui(!searching, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.toggle_value(&mut self.show_clues, "Clues");
+                                ui.toggle_value(&mut self.show_stats, "Stats");
+                                let play_enabled = playing || self.can_play();
+                                if ui.add_enabled(play_enabled, egui::SelectableLabel::new(playing, "Play"))
+                                    .on_hover_text("Solve the puzzle with the answers hidden").clicked() {
+                                    self.toggle_play();
+                                }
+                                if ui.add_enabled(!playing && !self.undo_stack.is_empty(), egui::Button::new("Undo"))
+                                    .on_hover_text("Ctrl+Z").clicked() {
+                                    self.undo();
+                                }
+                                if ui.add_enabled(!playing && !self.redo_stack.is_empty(), egui::Button::new("Redo"))
+                                    .on_hover_text("Ctrl+Y").clicked() {
+                                    self.redo();
+                                }
+                                ui.separator();
+                                let cells = self.regenerate_cells();
+                                if ui.add_enabled(!playing && !cells.is_empty(), egui::Button::new("Regenerate"))
+                                    .on_hover_text("Fill again the selected word or region, keeping the rest of the grid").clicked() {
+                                    self.regenerate(ui.ctx(), cells);
+                                }
+
+                                // letters of the selected cell when it is a rebus cell
+                                let rebus_cell = self.selected.filter(|&(x, y)| !playing && !is_block(self.cell(x, y)));
+                                if self.rebus_cell != rebus_cell {
+                                    self.rebus_cell = rebus_cell;
+                                    self.rebus_edit = rebus_cell.and_then(|cell| self.rebus.get(&cell).cloned()).unwrap_or_default();
+                                }
+                                if let Some((x, y)) = rebus_cell {
+                                    ui.separator();
+                                    ui.label("Rebus:");
+                                    let response = ui.add(egui::TextEdit::singleline(&mut self.rebus_edit).desired_width(60.0))
+                                        .on_hover_text("Letters of the selected cell, empty for a single letter");
+                                    if response.lost_focus() {
+                                        let letters = self.rebus_edit.clone();
+                                        self.set_rebus(x, y, &letters);
+                                        self.rebus_edit = self.rebus.get(&(x, y)).cloned().unwrap_or_default();
+                                    }
+                                }
+                            });
+                        });
+                        if self.play.is_some() {
+                            self.show_play_bar(ui);
+                        }
+
+                        // While searching the grid shows the latest snapshot and cannot be edited
+                        let progress_board = self.search.as_ref()
+                            .and_then(|search| search.progress.as_ref())
+                            .map(|progress| &progress.board);
+                        let selected_word = self.selected_word();
+                        let region = if self.edit_mode == EditMode::Region {self.region_cells()} else {Vec::new()};
+                        let play = self.play.as_ref();
+                        let mut clicked = None;
+                        let mut clicked_edge = None;
+
+                        // Clues of an arrowword, the words without a clue cell are highlighted
+                        let board = self.board();
+                        let (arrow_clues, no_clue_cell) = match arrowword::arrow_clues(&board, &self.definitions) {
+                            _ if !board.has_clue_cells() => (Vec::new(), Vec::new()),
+                            Ok(clues) => (clues, Vec::new()),
+                            Err(missing) => (Vec::new(), missing.iter().flat_map(|wp| (0..wp.len).map(|k| wp.cell(k))).collect()),
+                        };
                         egui::ScrollArea::both().auto_shrink([false; 2]).show(ui, |ui| {
                             ui.vertical_centered(|ui| {
-                                for j in 0..self.grid.len() {
-                                    let v = &mut self.grid[j];
+                                if searching {
+                                    ui.disable();
+                                }
+                                for (j, (v, l)) in self.grid.iter().zip(self.locked.iter()).enumerate() {
                                     ui.horizontal(|ui| {
-                                        for i in 0..v.len() {
-                                            let e = v[i];
+                                        for (i, (cell, lock)) in v.iter().zip(l.iter()).enumerate() {
+                                            let e = progress_board.or(play.map(Play::entries))
+                                                .and_then(|board| board.get(i, j).ok())
+                                                .unwrap_or(*cell);
+                                            let rebus = progress_board.or(play.map(Play::entries)).unwrap_or(&board).rebus(i, j);
                                             
                                             // Build definitions string
                                             let mut def_string: String = "".to_owned();
-                                            if !self.definitions.is_empty() {
+                                            let cell_clues: Vec<&ArrowClue> = arrow_clues.iter().filter(|c| (c.x, c.y) == (i, j)).collect();
+                                            if e == CLUE_CELL {
+                                                def_string = cell_clues.iter()
+                                                    .map(|c| format!("{} {}", c.arrow.symbol(), c.text))
+                                                    .collect::<Vec<String>>()
+                                                    .join("\n");
+                                            }
+                                            else if no_clue_cell.contains(&(i, j)) {
+                                                def_string.push_str("No clue cell");
+                                            }
+                                            else if !self.definitions.is_empty() {
                                                 for (wp, def) in &self.definitions {
                                                     if wp.x == i && wp.y == j {
                                                         if !def_string.is_empty() {
-                                                            def_string.push_str("\n");
+                                                            def_string.push('\n');
                                                         }
                                                         def_string.push_str(if wp.dir == Dir::HOR {"Hor: "} else {"Ver: "});
                                                         def_string.push_str(def);
@@ -180,6 +1497,20 @@ impl<'a> eframe::App for BaseApp<'a> {
                                                 }
                                             }
 
+                                            // Add candidates of the slots of the cell
+                                            let counts = self.slot_counts.get(j).and_then(|r| r.get(i)).copied()
+                                                .filter(|_| !searching && play.is_none())
+                                                .unwrap_or([None; 2]);
+                                            for (dir, count) in ["Hor", "Ver"].iter().zip(counts) {
+                                                if let Some(count) = count {
+                                                    if !def_string.is_empty() {
+                                                        def_string.push('\n');
+                                                    }
+                                                    def_string.push_str(&format!("{}: {} candidates", dir, count));
+                                                }
+                                            }
+                                            let min_count = counts.iter().flatten().min().copied();
+
                                             // Draw cell
                                             let response = ui
                                             .scope_builder(
@@ -189,24 +1520,64 @@ impl<'a> eframe::App for BaseApp<'a> {
                                                     let response = ui.response();
                                                     let visuals = ui.style().interact(&response);
                                                     
+                                                    let fill = if e == OUTSIDE_CELL {
+                                                        Color32::TRANSPARENT
+                                                    }
+                                                    else if e == '#' {
+                                                        Color32::BLACK
+                                                    }
+                                                    else if e == CLUE_CELL {
+                                                        CLUE_CELL_COLOR
+                                                    }
+                                                    else if region.contains(&(i, j)) {
+                                                        REGION_COLOR
+                                                    }
+                                                    else if self.selected == Some((i, j)) {
+                                                        SELECTED_COLOR
+                                                    }
+                                                    else if min_count == Some(0) || no_clue_cell.contains(&(i, j)) {
+                                                        DEAD_COLOR
+                                                    }
+                                                    else if min_count.is_some_and(|count| count < LOW_CANDIDATES) {
+                                                        LOW_COLOR
+                                                    }
+                                                    else if selected_word.contains(&(i, j)) {
+                                                        WORD_COLOR
+                                                    }
+                                                    else {
+                                                        Color32::WHITE
+                                                    };
+
                                                     Frame::canvas(ui.style())
-                                                        .fill(if e == '#' {Color32::BLACK} else {Color32::WHITE})
-                                                        .stroke(visuals.bg_stroke)
+                                                        .fill(fill)
+                                                        .stroke(if e == OUTSIDE_CELL {egui::Stroke::NONE} else {visuals.bg_stroke})
                                                         .inner_margin(ui.spacing().menu_margin)
                                                         .show(ui, |ui| {
                                                             ui.set_width(16.0);
                                                             ui.set_height(16.0);
                                                             
                                                             ui.vertical_centered(|ui| {
+                                                                // clue cells show the arrows of their clues
+                                                                let text = match e {
+                                                                    '#' | OUTSIDE_CELL => " ".to_owned(),
+                                                                    CLUE_CELL => cell_clues.iter().map(|c| c.arrow.symbol()).collect(),
+                                                                    e => rebus.map_or(e.to_string(), str::to_owned),
+                                                                };
                                                                 let response = Label::new(
-                                                                    RichText::new(if e != '#' {e} else {' '})
-                                                                        .color(Color32::BLACK)
-                                                                        .size(16.0)
+                                                                    RichText::new(text)
+                                                                        .color(match play {
+                                                                            Some(play) if play.is_wrong(i, j) => WRONG_COLOR,
+                                                                            Some(play) if play.is_revealed(i, j) => REVEALED_COLOR,
+                                                                            Some(_) => Color32::BLACK,
+                                                                            None if *lock => LOCKED_COLOR,
+                                                                            None => Color32::BLACK,
+                                                                        })
+                                                                        .size(if e == CLUE_CELL || rebus.is_some() {10.0} else {16.0})
                                                                 ).ui(ui);
                                                                 
-                                                                // switch from black to white and viceversa
+                                                                // select or switch the cell
                                                                 if response.clicked() {
-                                                                    v[i] = if e == '#' {' '} else {'#'};
+                                                                    clicked = Some((i, j));
                                                                 }
                                                                 
                                                                 // show definition
@@ -219,9 +1590,24 @@ impl<'a> eframe::App for BaseApp<'a> {
                                             )
                                             .response;
                                             
-                                            // switch from black to white and viceversa
+                                            // select or switch the cell
                                             if response.clicked() {
-                                                v[i] = if e == '#' {' '} else {'#'};
+                                                clicked = Some((i, j));
+                                            }
+                                            if clicked == Some((i, j)) {
+                                                clicked_edge = ui.input(|i| i.pointer.interact_pos())
+                                                    .map(|pos| nearest_edge(response.rect, pos));
+                                            }
+
+                                            // thick bars on the right and bottom edges, in the middle of the spacing
+                                            let spacing = ui.spacing().item_spacing / 2.0;
+                                            let stroke = egui::Stroke::new(BAR_WIDTH, Color32::BLACK);
+                                            let rect = response.rect.expand2(spacing);
+                                            if self.bars[j][i][Dir::HOR as usize] {
+                                                ui.painter().line_segment([rect.right_top(), rect.right_bottom()], stroke);
+                                            }
+                                            if self.bars[j][i][Dir::VER as usize] {
+                                                ui.painter().line_segment([rect.left_bottom(), rect.right_bottom()], stroke);
                                             }
 
                                             // show definition
@@ -233,7 +1619,11 @@ impl<'a> eframe::App for BaseApp<'a> {
                                 }
                             });
                         });
-                        
+
+                        if let Some((x, y)) = clicked {
+                            let extend = ui.input(|i| i.modifiers.shift);
+                            self.click_cell(x, y, extend, clicked_edge);
+                        }
                     });
                     strip.cell(|ui| {
                         let button_width = 128.0;
@@ -244,51 +1634,32 @@ impl<'a> eframe::App for BaseApp<'a> {
                             ui.horizontal(|ui| {
                                 ui.add_space(padding);
 
-                                // GENERATE
-                                let response = ui.add_sized((128.0, 48.0), egui::Button::new("Generate!"));
-                                if response.clicked() {
-                                    // Clean grid
-                                    for j in 0..self.grid.len() {
-                                        let v = &mut self.grid[j];
-                                        for i in 0..v.len() {
-                                            if v[i] != '#' {
-                                                v[i] = ' ';
-                                            }
-                                        }
-                                    }
-    
-                                    // Create board
-                                    let mut board = Board::new(self.width, self.height);
-    
-                                    // Add black cells
-                                    for j in 0..self.grid.len() {
-                                        let v = &self.grid[j];
-                                        for i in 0..v.len() {
-                                            board.set(i, j, v[i]);
-                                        }
-                                    }
-    
-                                    // Process
-                                    self.result = Some(generate(&mut board, self.words_len.clone(), self.shuffle, self.rep_words));
-
-                                    // Update definitions
-                                    if self.result.as_ref().unwrap().found {
-                                        self.definitions = get_definitions(&board, self.words_def);
+                                // GENERATE / STOP
+                                if searching {
+                                    let response = ui.add_sized((128.0, 48.0), egui::Button::new("Stop"));
+                                    if response.clicked() {
+                                        self.stop_search();
                                     }
-    
-                                    // Update grid with board data
-                                    for j in 0..self.grid.len() {
-                                        let v = &mut self.grid[j];
-                                        for i in 0..v.len() {
-                                            v[i] = board.get(i, j);
-                                        }
-                                    }
-                                };
+                                }
+                                else {
+                                    let response = ui.add_enabled_ui(!playing, |ui| {
+                                        ui.add_sized((128.0, 48.0), egui::Button::new("Generate!"))
+                                    }).inner;
+                                    if response.clicked() {
+                                        self.generate(ui.ctx());
+                                    };
+                                }
     
                                 // RESET
-                                let response = ui.add_sized((128.0, 48.0), egui::Button::new("Reset"));
+                                let response = ui.add_enabled_ui(!searching && !playing, |ui| {
+                                    ui.add_sized((128.0, 48.0), egui::Button::new("Reset"))
+                                }).inner;
                                 if response.clicked() {
+                                    self.checkpoint();
                                     self.grid = vec![vec![' '; self.width]; self.height];
+                                    self.locked = vec![vec![false; self.width]; self.height];
+                                    self.bars = vec![vec![[false; 2]; self.width]; self.height];
+                                    self.rebus.clear();
                                     self.definitions.clear();
                                 }
                             });
@@ -0,0 +1,47 @@
+//! Crosswords Generator: fill a crossword board with the words of a dictionary.
+//!
+//! The solver does not depend on the GUI or the CLI, which are behind the `gui` and `cli` features.
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//! use crosswords_generator::{generate, get_definitions, Board, Dictionary, GenerateOptions};
+//!
+//! let mut words = BTreeMap::new();
+//! for word in ["AB", "CD", "AC", "BD"] {
+//!     words.insert(word.to_owned(), vec![format!("Definition of {}", word)]);
+//! }
+//! let dictionary = Dictionary::new(words);
+//!
+//! let mut board = Board::new(2, 2);
+//! let options = GenerateOptions::new().shuffle(true).seed(7);
+//! let solution = generate(&mut board, &dictionary.index(), &options).unwrap();
+//!
+//! assert!(solution.found);
+//! let definitions = get_definitions(&board, &dictionary).unwrap();
+//! assert_eq!(definitions.len(), 4);
+//! ```
+
+pub mod arrowword;
+pub mod batch;
+pub mod codeword;
+pub mod crisscross;
+pub mod dictionary;
+pub mod error;
+pub mod export;
+pub mod fillin;
+pub mod generator;
+pub mod mask;
+pub mod output;
+pub mod play;
+pub mod project;
+pub mod stats;
+pub mod utils;
+
+pub use dictionary::{Dictionary, Translations, WordIndex};
+pub use error::Error;
+pub use generator::{count_candidates, find_solutions, generate, generate_with_progress, get_candidates, get_definitions, get_short_definitions, keep_definitions,
+                    regenerate, regenerate_with_progress, word_squares, Candidate, GenerateOptions, Progress, WordSquare};
+pub use play::Play;
+pub use project::{Project, Rebus};
+pub use stats::GridStats;
+pub use utils::{Board, Dir, Solution, Symmetry, WordPos};
@@ -1,27 +1,31 @@
 // #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
+#[cfg(feature = "gui")]
 use eframe::egui;
-use utils::Solution;
 use std::collections::HashMap;
-use std::collections::HashSet;
-use std::fs;
-use std::time::SystemTime;
+use std::process::ExitCode;
+use std::time::Instant;
 
 use rand::Rng;
-use rand::thread_rng;
-use rand::seq::SliceRandom;
+use serde::Serialize;
 
-use clap::{command, Arg};
+use clap::{command, parser::ValueSource, Arg, Command};
 
-use utils::{Board, WordPos, Dir};
+use crosswords_generator::{arrowword, batch, codeword, crisscross, export, fillin, output};
+use crosswords_generator::{generate_with_progress, get_definitions, get_short_definitions, keep_definitions, regenerate_with_progress};
+use crosswords_generator::{word_squares, Board, Dictionary, Error, Translations, GenerateOptions, GridStats, Progress, Project, WordIndex, WordPos, WordSquare};
+use crosswords_generator::arrowword::ArrowClue;
+use crosswords_generator::batch::BatchSettings;
+use crosswords_generator::mask::Mask;
+use crosswords_generator::project::DEFAULT_DICTIONARY;
+use crosswords_generator::utils::is_block;
 
+#[cfg(feature = "gui")]
 use gui::BaseApp;
 
-mod utils;
+#[cfg(feature = "gui")]
 mod gui;
 
-static EMPTY_VEC: Vec<&str> = Vec::new();
-
 #[derive(Debug, PartialEq, Eq)]
 pub enum Fs {
     FWD,
@@ -29,22 +33,36 @@ pub enum Fs {
     BWD
 }
 
-fn main() -> eframe::Result {
-    const VERSION: &str = env!("CARGO_PKG_VERSION");
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// Minimum time in ms between two progress reports of the CLI
+const CLI_PROGRESS_INTERVAL: u64 = 5000;
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::from(e.exit_code())
+        },
+    }
+}
 
+fn run() -> Result<(), Error> {
     // Check arguments
     let args = command!().about(format!("Crosswords Generator v{}\nSmall application to fill a provided Crossword Board.", VERSION))
     .arg(
         Arg::new("no-gui").short('g').long("no-gui")
         .help("Use CLI to generate crosswords.")
         .num_args(0..=1)
-        .value_parser(["true", "false"])
+        .value_parser(clap::value_parser!(bool))
         .default_value("false")
         .default_missing_value("true")
     )
     .arg(
         Arg::new("size").short('s').long("size")
         .help("Size of the board.")
+        .global(true)
         .num_args(2)
         .value_parser(clap::value_parser!(usize))
         .default_values(["5", "5"])
@@ -52,300 +70,793 @@ fn main() -> eframe::Result {
     )
     .arg(
         Arg::new("board").short('b').long("board")
-        .help("Path to a board.")
+        .help("Path to a project file to use as board.")
+        .global(true)
+    )
+    .arg(
+        Arg::new("mask").long("mask")
+        .help("Shape of the board: a text file ('.' outside the shape), a PNG image (dark pixels inside, scaled to the size) or 'circle'.")
+        .conflicts_with("board")
+    )
+    .arg(
+        Arg::new("dictionary").short('d').long("dictionary")
+        .help("Path to the dictionary of words and definitions.")
+        .global(true)
+        .default_value(DEFAULT_DICTIONARY)
+    )
+    .arg(
+        Arg::new("translations").short('t').long("translations")
+        .help("Path to the translations of the words (json object word -> translations), the clues are the translations.")
+        .global(true)
+    )
+    .arg(
+        Arg::new("reverse-translations").long("reverse-translations")
+        .help("Use the translations in the other direction: the answers are the translated words.")
+        .global(true)
+        .num_args(0..=1)
+        .value_parser(clap::value_parser!(bool))
+        .default_value("false")
+        .default_missing_value("true")
+    )
+    .arg(
+        Arg::new("seed").long("seed")
+        .help("Seed used to shuffle the words (random if not set).")
+        .global(true)
+        .value_parser(clap::value_parser!(u64))
+    )
+    .arg(
+        Arg::new("output").short('o').long("output")
+        .help("Save the generated puzzle as a project file.")
+    )
+    .arg(
+        Arg::new("svg").long("svg")
+        .help("Export the empty grid of the generated puzzle as an SVG image.")
+    )
+    .arg(
+        Arg::new("puz").long("puz")
+        .help("Export the generated puzzle as an Across Lite .puz file.")
+    )
+    .arg(
+        Arg::new("ipuz").long("ipuz")
+        .help("Export the generated puzzle as an ipuz file.")
+    )
+    .arg(
+        Arg::new("regenerate").long("regenerate")
+        .help("Regenerate only the given words of the project solution, e.g. 1A,3D.")
+        .num_args(1..)
+        .value_delimiter(',')
+        .requires("board")
+    )
+    .arg(
+        Arg::new("square").long("square")
+        .help("Fill the board as a word square: row i equal to column i (single) or all different words (double).")
+        .value_parser(["none", "single", "double"])
+        .default_value("none")
     )
     .arg(
         Arg::new("shuffle").short('x').long("shuffle")
         .help("Shuffle the words before filling the board.")
         .num_args(0..=1)
-        .value_parser(["true", "false"])
+        .value_parser(clap::value_parser!(bool))
         .default_value("false")
         .default_missing_value("true")
     )
     .arg(
         Arg::new("repeat-words").short('r').long("repeat-words")
         .help("Allow words to be repeated.")
+        .global(true)
         .num_args(0..=1)
-        .value_parser(["true", "false"])
+        .value_parser(clap::value_parser!(bool))
         .default_value("false")
         .default_missing_value("true")   
     )
+    .arg(
+        Arg::new("format").short('f').long("format")
+        .help("Output format of the CLI, json implies --no-gui.")
+        .global(true)
+        .value_parser(["text", "json"])
+        .default_value("text")
+    )
+    .subcommand(
+        Command::new("batch")
+        .about("Generate many puzzles in one run, using the board or a generated pattern.")
+        .arg(
+            Arg::new("count").short('n').long("count")
+            .help("Number of puzzles to generate.")
+            .value_parser(clap::value_parser!(usize))
+            .default_value("10")
+        )
+        .arg(
+            Arg::new("out-dir").short('o').long("out-dir")
+            .help("Directory where puzzles and report are written.")
+            .required(true)
+        )
+        .arg(
+            Arg::new("black-density").long("black-density")
+            .help("Fraction of black cells in generated patterns (ignored with --board).")
+            .value_parser(clap::value_parser!(f64))
+            .default_value("0.0")
+        )
+        .arg(
+            Arg::new("symmetric").long("symmetric")
+            .help("Generate patterns with 180° rotational symmetry.")
+            .num_args(0..=1)
+            .value_parser(clap::value_parser!(bool))
+            .default_value("true")
+            .default_missing_value("true")
+        )
+        .arg(
+            Arg::new("unique-answers").short('u').long("unique-answers")
+            .help("Do not reuse the same answers across the batch.")
+            .num_args(0..=1)
+            .value_parser(clap::value_parser!(bool))
+            .default_value("false")
+            .default_missing_value("true")
+        )
+    )
+    .subcommand(
+        Command::new("fillin")
+        .about("Make a fill-in puzzle from the solution of the project and check that it is unique.")
+        .arg(
+            Arg::new("starters").long("starters")
+            .help("Maximum number of starter letters given to make the solution unique.")
+            .value_parser(clap::value_parser!(usize))
+            .default_value("0")
+        )
+        .arg(
+            Arg::new("words").short('w').long("words")
+            .help("Save the word list grouped by length as a text file.")
+        )
+        .arg(
+            Arg::new("svg").long("svg")
+            .help("Export the grid with the starter letters as an SVG image.")
+        )
+    )
+    .subcommand(
+        Command::new("codeword")
+        .about("Make a codeword puzzle from the solution of the project, revealing a minimal set of letters.")
+        .arg(
+            Arg::new("svg").long("svg")
+            .help("Export the numbered grid and the letter key as an SVG image.")
+        )
+    )
+    .subcommand(
+        Command::new("diagramless")
+        .about("Make a diagramless puzzle from the solution of the project: an empty grid and the numbered clues.")
+        .arg(
+            Arg::new("hint").long("hint")
+            .help("Give the starting square of each clue and number the first one in the grid.")
+            .num_args(0..=1)
+            .value_parser(clap::value_parser!(bool))
+            .default_value("false")
+            .default_missing_value("true")
+        )
+        .arg(
+            Arg::new("svg").long("svg")
+            .help("Export the empty grid as an SVG image.")
+        )
+        .arg(
+            Arg::new("solution").long("solution")
+            .help("Export the solution with the black cells and the numbers as an SVG image.")
+        )
+        .arg(
+            Arg::new("clues").long("clues")
+            .help("Save the clue list as a text file.")
+        )
+    )
+    .subcommand(
+        Command::new("squares")
+        .about("List the word squares of the given size found in the dictionary.")
+        .arg(
+            Arg::new("double").long("double")
+            .help("Double word squares: rows and columns are all different words.")
+            .num_args(0..=1)
+            .value_parser(clap::value_parser!(bool))
+            .default_value("false")
+            .default_missing_value("true")
+        )
+        .arg(
+            Arg::new("limit").short('n').long("limit")
+            .help("Maximum number of squares listed.")
+            .value_parser(clap::value_parser!(usize))
+            .default_value("20")
+        )
+    )
+    .subcommand(
+        Command::new("crisscross")
+        .about("Lay out a list of words in a free-form interlocking grid, the clues come from the dictionary.")
+        .arg(
+            Arg::new("words").short('w').long("words")
+            .help(format!("Text file with the words to lay out, one per line (at most {} words).", crisscross::MAX_WORDS))
+            .required(true)
+        )
+        .arg(
+            Arg::new("attempts").short('n').long("attempts")
+            .help("Orders of the words tried, the most compact layout is kept.")
+            .value_parser(clap::value_parser!(usize))
+            .default_value("200")
+        )
+        .arg(
+            Arg::new("output").short('o').long("output")
+            .help("Save the layout as a project file.")
+        )
+        .arg(
+            Arg::new("svg").long("svg")
+            .help("Export the empty grid as an SVG image.")
+        )
+    )
     .get_matches();
 
     // Settings
-    let no_gui = args.get_one::<String>("no-gui").unwrap()
-        .parse::<bool>().unwrap_or_else(|e| panic!("Argument 'no-gui' error: {}", e));
+    let json_output = args.get_one::<String>("format").unwrap() == "json";
+    let no_gui = json_output || *args.get_one::<bool>("no-gui").unwrap();
     let size: Vec<usize> = args.get_many("size").unwrap().copied().collect();
-    let board_w = *size.get(0).unwrap();
-    let board_h = *size.get(1).unwrap();
+    let mut board_w = *size.first().unwrap();
+    let mut board_h = *size.get(1).unwrap();
     let board_path = args.get_one::<String>("board");
-    let shuffle = args.get_one::<String>("shuffle").unwrap()
-        .parse::<bool>().unwrap_or_else(|e| panic!("Argument 'shuffle' error: {}", e));
-    let rep_words = args.get_one::<String>("repeat-words").unwrap()
-        .parse::<bool>().unwrap_or_else(|e| panic!("Argument 'repeat-words' error: {}", e));
+    let mut dictionary = args.get_one::<String>("dictionary").unwrap().clone();
+    let mut translations = args.get_one::<String>("translations").cloned();
+    let mut reverse_translations = *args.get_one::<bool>("reverse-translations").unwrap();
+    let mut shuffle = *args.get_one::<bool>("shuffle").unwrap();
+    let mut rep_words = *args.get_one::<bool>("repeat-words").unwrap();
+    let mut seed = args.get_one::<u64>("seed").copied();
+    let output_path = args.get_one::<String>("output");
+    let svg_path = args.get_one::<String>("svg");
+    let puz_path = args.get_one::<String>("puz");
+    let ipuz_path = args.get_one::<String>("ipuz");
+    let square = match args.get_one::<String>("square").unwrap().as_str() {
+        "single" => WordSquare::Single,
+        "double" => WordSquare::Double,
+        _ => WordSquare::None,
+    };
+    let regenerate_ids: Option<Vec<String>> = args.get_many::<String>("regenerate").map(|ids| ids.cloned().collect());
+
+    // Load project, settings passed on the command line take precedence
+    let from_cli = |id: &str| args.value_source(id) == Some(ValueSource::CommandLine);
+    let project = board_path.map(|path| Project::load(path)).transpose()?;
+    if let Some(project) = &project {
+        board_w = project.width;
+        board_h = project.height;
+        if !from_cli("dictionary") {
+            dictionary = project.dictionary.clone();
+        }
+        if !from_cli("translations") {
+            translations = project.translations.clone();
+        }
+        if !from_cli("reverse-translations") {
+            reverse_translations = project.reverse_translations;
+        }
+        if !from_cli("shuffle") {
+            shuffle = project.shuffle;
+        }
+        if !from_cli("repeat-words") {
+            rep_words = project.repeat_words;
+        }
+        if seed.is_none() {
+            seed = Some(project.seed);
+        }
+    }
+    let seed = seed.unwrap_or_else(|| rand::thread_rng().gen());
+
+    // Shape of the board, a text mask has its own size
+    let mask = args.get_one::<String>("mask").map(|path| match path.as_str() {
+        "circle" => Ok(Mask::ellipse(board_w, board_h)),
+        path => Mask::load(path, board_w, board_h),
+    }).transpose()?;
+    if let Some(mask) = &mask {
+        board_w = mask.width();
+        board_h = mask.height();
+    }
     
 
     // CROSSWORDS GENERATOR
-    println!("Crosswords Generator v{}", VERSION);
+    if !json_output {
+        println!("Crosswords Generator v{}", VERSION);
+    }
+
+    // Load json words and definitions, the dictionary lives until the end of the program
+    // so that the GUI can search in a worker thread
+    let time_json = Instant::now();
+    let mut words_def = Dictionary::load(&dictionary)?;
 
-    // Load json words and definitions
-    let time_json = SystemTime::now();
-    let json = load_words("./data/words.txt");
-    println!("Time to read and parse json: {} ms", time_json.elapsed().unwrap().as_millis());
+    // Bilingual crosswords: only the words with a translation, clued by it
+    if let Some(path) = &translations {
+        let mut words = Translations::load(path)?;
+        if reverse_translations {
+            words = words.reversed();
+        }
+        words_def = words_def.translated(&words);
+        if words_def.is_empty() {
+            return Err(Error::Dictionary { path: path.clone(), reason: "no word of the dictionary has a translation".to_owned() });
+        }
+    }
+    let words_def: &'static Dictionary = Box::leak(Box::new(words_def));
+    if !json_output {
+        println!("Time to read and parse json: {} ms", time_json.elapsed().as_millis());
+    }
 
     // Create map (len -> words)
-    let time_maplen = SystemTime::now();
-    let mut words_len: HashMap<usize, Vec<&str>> = HashMap::new();
-    for (key, _) in json.as_object().unwrap() {
-        let len = key.len();
-        words_len.entry(len).or_insert_with(Vec::new).push(key);
+    let time_maplen = Instant::now();
+    let words_len: WordIndex = words_def.index();
+    if !json_output {
+        println!("Time to create the map (len->words): {} ms", time_maplen.elapsed().as_millis());
     }
-    println!("Time to create the map (len->words): {} ms", time_maplen.elapsed().unwrap().as_millis());
-    
-    // CLI
-    if no_gui {
-        println!("\nSettings:");
-        println!("- size: {:?}x{:?}", board_w, board_h);
-        println!("- board: {:?}", board_path);
-        println!("- shuffle: {}", shuffle);
-        println!("- repeat-words: {}", rep_words);
-        println!();
-        
-        // Create board
-        let mut board = Board::new(board_w, board_h);
-        //board.set(0, 0, '#');
-        
-        // Find solution
-        let sol = generate(&mut board, words_len, shuffle, rep_words);
 
-        // Solution found
-        if sol.found {
-            board.print();
-            println!("Time to fill the board: {} ms", sol.time_elapsed);
-            print_definitions(get_definitions(&board, &json));
+    // BATCH
+    if let Some(("batch", batch_args)) = args.subcommand() {
+        let settings = BatchSettings {
+            count: *batch_args.get_one::<usize>("count").unwrap(),
+            out_dir: batch_args.get_one::<String>("out-dir").unwrap().clone(),
+            template: project,
+            width: board_w,
+            height: board_h,
+            black_density: *batch_args.get_one::<f64>("black-density").unwrap(),
+            symmetric: *batch_args.get_one::<bool>("symmetric").unwrap(),
+            unique_answers: *batch_args.get_one::<bool>("unique-answers").unwrap(),
+            rep_words,
+            seed,
+            dictionary,
+            translations,
+            reverse_translations,
+        };
+
+        if !json_output {
+            println!("\nBatch settings:");
+            println!("- count: {}", settings.count);
+            println!("- out-dir: {}", settings.out_dir);
+            println!("- board: {:?}", board_path);
+            println!("- size: {:?}x{:?}", board_w, board_h);
+            println!("- black-density: {}", settings.black_density);
+            println!("- symmetric: {}", settings.symmetric);
+            println!("- unique-answers: {}", settings.unique_answers);
+            println!("- seed: {}", seed);
+        }
+
+        let report = batch::run_batch(&settings, words_def)?;
+        if json_output {
+            print_json(&report)?;
         }
-        // Solution not found
         else {
-            println!("No solution found in: {} ms", sol.time_elapsed);
+            println!("\n{}", report);
         }
-    
-        // Print Visited Nodes
-        println!("\nSTATS");
-        println!("Visited nodes: {}", sol.visited_nodes);
 
-        Ok(())
+        return Ok(());
     }
 
-    // GUI
-    else {
-        init_gui(VERSION, words_len, &json)
-    }
-}
+    // FILL-IN
+    if let Some(("fillin", fi_args)) = args.subcommand() {
+        let board = solution_board(project.as_ref(), board_path)?;
 
+        let time_check = Instant::now();
+        let puzzle = fillin::fill_in(&board, *fi_args.get_one::<usize>("starters").unwrap())?;
+        if let Some(path) = fi_args.get_one::<String>("words") {
+            export::save_word_list(&puzzle.words, path)?;
+            if !json_output {
+                println!("Word list saved in: {}", path);
+            }
+        }
+        if let Some(path) = fi_args.get_one::<String>("svg") {
+            export::save_svg(&puzzle.grid, &[], true, path)?;
+            if !json_output {
+                println!("SVG saved in: {}", path);
+            }
+        }
 
-fn init_gui(ver: &str, words_len: HashMap<usize, Vec<&str>>, definitions: &serde_json::Value) -> eframe::Result {
-    env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
-    let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default().with_inner_size([640.0, 480.0]),
-        ..Default::default()
-    };
-
-    eframe::run_native(
-        format!("Crosswords Generator v{}", ver).as_str(),
-        options,
-        Box::new(|cc| {
-            egui_extras::install_image_loaders(&cc.egui_ctx); // support for images
-            //Ok(Box::<BaseApp>::default())
-            Ok(Box::new(BaseApp::new(&cc.egui_ctx, words_len, definitions)))
-        }),
-    )
-}
+        if json_output {
+            print_json(&output::FillInOutput {
+                version: VERSION,
+                grid: puzzle.grid.rows(),
+                words: &puzzle.words,
+                starters: &puzzle.starters,
+                unique: puzzle.unique,
+            })?;
+        }
+        else {
+            println!("{}", puzzle.grid.pretty());
+            println!("Time to check the solution: {} ms", time_check.elapsed().as_millis());
+            println!("Starter letters: {}", puzzle.starters.len());
+            println!("Unique solution: {}", puzzle.unique);
+            println!("\nWORDS\n{}", export::word_list(&puzzle.words));
+        }
 
+        return if puzzle.unique { Ok(()) } else { Err(Error::Solver("The fill-in puzzle has more than one solution".to_owned())) };
+    }
 
-fn load_words(path: &str) -> serde_json::Value {
-    let content: String = fs::read_to_string(path).expect("Unable to read text file");
-    let json: serde_json::Value = serde_json::from_str(content.as_str()).expect("JSON was not well-formatted");
-    json
-}
+    // CODEWORD
+    if let Some(("codeword", cw_args)) = args.subcommand() {
+        let board = solution_board(project.as_ref(), board_path)?;
 
+        let time_check = Instant::now();
+        let puzzle = codeword::codeword(&board, &words_len, seed);
+        if let Some(path) = cw_args.get_one::<String>("svg") {
+            export::save_codeword_svg(&board, &puzzle, path)?;
+            if !json_output {
+                println!("SVG saved in: {}", path);
+            }
+        }
 
-fn generate(board: &mut Board, mut words_len: HashMap<usize, Vec<&str>>, shuffle: bool, rep_words: bool) -> Solution {
-    // Randomize words
-    if shuffle {
-        for words in words_len.values_mut() {
-            words.shuffle(&mut thread_rng());
+        if json_output {
+            print_json(&output::CodewordOutput {
+                version: VERSION,
+                seed,
+                grid: (0..board.height())
+                    .map(|y| (0..board.width()).map(|x| puzzle.number(&board, x, y)).collect())
+                    .collect(),
+                cipher: &puzzle.cipher,
+                revealed: &puzzle.revealed,
+                unique: puzzle.unique,
+            })?;
+        }
+        else {
+            println!("\n{}", puzzle.pretty(&board));
+            println!("\nTime to check the solution: {} ms", time_check.elapsed().as_millis());
+            println!("Revealed letters: {}", puzzle.revealed.iter().collect::<String>());
+            println!("Unique solution: {}", puzzle.unique);
         }
+
+        return if puzzle.unique { Ok(()) } else { Err(Error::Solver("The codeword puzzle has more than one solution".to_owned())) };
     }
 
-    // Create list of missing word positions
-    let mut words_pos = board.get_words_pos();
-    words_pos.sort_by(|a, b| Ord::cmp(&a.len, &b.len));
-
-    // create map of word_pos -> intersecting word_pos
-    let mut words_intersect: HashMap<&WordPos, Vec<&WordPos>> = HashMap::new();
-    for word_pos in &words_pos {
-        match word_pos.dir {
-            Dir::HOR => {
-                let wi: Vec<&WordPos> = words_pos.iter().filter(|wp|
-                    wp.dir == Dir::VER &&
-                    wp.y <= word_pos.y &&
-                    wp.y + wp.len > word_pos.y).collect();
-                words_intersect.insert(word_pos, wi);
-            },
-            Dir::VER => {
-                let wi: Vec<&WordPos> = words_pos.iter().filter(|wp|
-                        wp.dir == Dir::HOR &&
-                        wp.x <= word_pos.x &&
-                        wp.x + wp.len > word_pos.x).collect();
-                words_intersect.insert(word_pos, wi);
-            }
+    // DIAGRAMLESS
+    if let Some(("diagramless", dl_args)) = args.subcommand() {
+        let board = solution_board(project.as_ref(), board_path)?;
+        let definitions = project.as_ref().map(Project::definitions).unwrap_or_default();
+        let hint = *dl_args.get_one::<bool>("hint").unwrap();
+
+        let mut saved = Vec::new();
+        if let Some(path) = dl_args.get_one::<String>("svg") {
+            export::save_diagramless_svg(&board, hint, path)?;
+            saved.push(("Grid", path));
+        }
+        if let Some(path) = dl_args.get_one::<String>("solution") {
+            export::save_svg(&board, &[], true, path)?;
+            saved.push(("Solution", path));
+        }
+        if let Some(path) = dl_args.get_one::<String>("clues") {
+            export::save_diagramless_clues(&board, &definitions, hint, path)?;
+            saved.push(("Clues", path));
         }
-    }
 
-    // fill board
-    let found;
-    let time_fill = SystemTime::now();
-    let mut visited_nodes: u64 = 0;
-
-    // recursive
-    found = fill_board(board, &words_len, &words_pos, &words_intersect,
-        &mut HashSet::with_capacity(words_pos.len()),
-        &mut HashMap::new(), &mut visited_nodes,
-        rep_words);
-
-    Solution {
-        found,
-        time_elapsed: time_fill.elapsed().unwrap().as_millis(),
-        visited_nodes,
+        if json_output {
+            print_json(&output::DiagramlessOutput {
+                version: VERSION,
+                width: board.width(),
+                height: board.height(),
+                entries: output::get_entries(&board, &definitions),
+                solution: board.rows(),
+            })?;
+        }
+        else {
+            println!("\n{}", export::diagramless_clues(&board, &definitions, hint));
+            for (page, path) in saved {
+                println!("{} saved in: {}", page, path);
+            }
+        }
+        return Ok(());
     }
-}
 
+    // WORD SQUARES
+    if let Some(("squares", sq_args)) = args.subcommand() {
+        let square = if *sq_args.get_one::<bool>("double").unwrap() {WordSquare::Double} else {WordSquare::Single};
+        let options = GenerateOptions::new().shuffle(shuffle).seed(seed).repeat_words(rep_words).square(square);
+        let time_search = Instant::now();
+        let squares = word_squares(board_w, board_h, &words_len, &options, *sq_args.get_one::<usize>("limit").unwrap())?;
 
-fn fill_board<'a>(board: &mut Board, words_len: &'a HashMap<usize, Vec<&'a str>>, words_pos: &[WordPos],
-                    words_intersect: &HashMap<&WordPos, Vec<&WordPos>>, words_used: &mut HashSet<&'a str>,
-                    words_map_cache: &mut HashMap<String, Vec<&'a str>>, visited_nodes: &mut u64,
-                    rep_words: bool) -> bool {
-    if words_pos.is_empty() {
-        return true;
-    }
-    let mut valid = false;
-    let current_word_pos = words_pos.last().unwrap();
-    let current_word_board = board.get_word(current_word_pos);
-
-    // get valid words from cache if possible otherwise update cache
-    let valid_words = words_map_cache.entry(current_word_board.clone()).or_insert_with(|| {
-        get_valid_words(words_len.get(&current_word_pos.len).unwrap_or(&EMPTY_VEC), current_word_board.as_str())
-    }).clone();
-
-    // loop thorugh all valid words
-    for current_word in valid_words {
-        // check if the word has been used
-        if !rep_words {
-            if words_used.contains(current_word) {
-                continue;
+        if json_output {
+            print_json(&squares.iter().map(Board::rows).collect::<Vec<Vec<String>>>())?;
+        }
+        else {
+            for square in &squares {
+                println!("\n{}", square.rows().join("\n"));
             }
+            println!("\nSquares found: {} in {} ms", squares.len(), time_search.elapsed().as_millis());
         }
 
-        // set word in the board
-        board.set_word(current_word_pos, current_word);
+        return if squares.is_empty() {
+            Err(Error::Solver(format!("No word squares of size {}x{}", board_w, board_h)))
+        } else { Ok(()) };
+    }
 
-        // debug
-        *visited_nodes += 1;
-        if *visited_nodes % 10_000_000 == 0 {
-            board.print();
-            println!("Visited nodes: {}M\n", *visited_nodes / 1_000_000);
+    // CRISS-CROSS
+    if let Some(("crisscross", cc_args)) = args.subcommand() {
+        // the words of the list are matched to the dictionary ignoring the case
+        let list = crisscross::load_words(cc_args.get_one::<String>("words").unwrap())?;
+        let keys: HashMap<String, &str> = words_def.words().map(|key| (key.to_uppercase(), key)).collect();
+        let words = list.iter()
+            .map(|word| keys.get(&word.to_uppercase()).copied().ok_or_else(|| Error::MissingDefinitions(word.clone())))
+            .collect::<Result<Vec<&str>, Error>>()?;
+        let options = crisscross::CrissCrossOptions::new()
+            .attempts(*cc_args.get_one::<usize>("attempts").unwrap())
+            .seed(seed);
+        let time_layout = Instant::now();
+        let layout = crisscross::criss_cross(&words, &options);
+        let board = layout.board;
+        let definitions = get_definitions(&board, words_def)?;
+
+        if let Some(path) = cc_args.get_one::<String>("output") {
+            let mut output = Project::new(board.width(), board.height());
+            output.template = board.rows().iter()
+                .map(|r| r.chars().map(|c| if is_block(c) {c} else {' '}).collect())
+                .collect();
+            output.set_solution(&board, &definitions);
+            output.seed = seed;
+            output.dictionary = dictionary.clone();
+            output.translations = translations.clone();
+            output.reverse_translations = reverse_translations;
+            output.save(path)?;
+            if !json_output {
+                println!("Project saved in: {}", path);
+            }
+        }
+        if let Some(path) = cc_args.get_one::<String>("svg") {
+            export::save_svg(&board, &[], false, path)?;
+            if !json_output {
+                println!("SVG saved in: {}", path);
+            }
         }
 
-        // check that exists at least one intersecting word for each letter of the current word
-        let mut sol = true;
-        for word_pos_intersect in words_intersect.get(current_word_pos).unwrap() {
-            let word_board_intersect = board.get_word(word_pos_intersect);
-            let words_intersect_num: usize;
-
-            // get valid words from cache if possible otherwise create new vec and update cache
-            if let Some(valid_words_cached) = words_map_cache.get(&word_board_intersect) {
-                words_intersect_num = valid_words_cached.len();
+        if json_output {
+            print_json(&output::CrissCrossOutput {
+                version: VERSION,
+                seed,
+                grid: board.rows(),
+                entries: output::get_entries(&board, &definitions),
+                intersections: layout.intersections,
+                unplaced: layout.unplaced,
+                stats: GridStats::new(&board),
+            })?;
+        }
+        else {
+            println!("{}", board.pretty());
+            println!("Time to lay out the words: {} ms", time_layout.elapsed().as_millis());
+            println!("Intersections: {}", layout.intersections);
+            if !layout.unplaced.is_empty() {
+                println!("Words not placed: {}", layout.unplaced.join(", "));
             }
-            else {
-                let valid_words_intersect = get_valid_words(words_len.get(&word_pos_intersect.len).unwrap(), word_board_intersect.as_str());
-                words_intersect_num = valid_words_intersect.len();
+            print_definitions(definitions);
+            println!("\nSTATS");
+            println!("{}", GridStats::new(&board));
+        }
 
-                words_map_cache.insert(word_board_intersect, valid_words_intersect);
+        return Ok(());
+    }
+    
+    // CLI
+    if no_gui {
+        if !json_output {
+            println!("\nSettings:");
+            println!("- size: {:?}x{:?}", board_w, board_h);
+            println!("- board: {:?}", board_path);
+            println!("- dictionary: {}", dictionary);
+            if let Some(path) = &translations {
+                println!("- translations: {}{}", path, if reverse_translations {" (reversed)"} else {""});
             }
+            println!("- shuffle: {}", shuffle);
+            println!("- seed: {}", seed);
+            println!("- repeat-words: {}", rep_words);
+            if let Some(ids) = &regenerate_ids {
+                println!("- regenerate: {}", ids.join(","));
+            }
+            println!();
+        }
+        
+        // Create board
+        let mut board = match &project {
+            Some(project) => project.board()?,
+            None => Board::new(board_w, board_h),
+        };
+        if let Some(mask) = &mask {
+            mask.apply(&mut board);
+        }
 
-            // stop if there are no valid words
-            if words_intersect_num == 0 {
-                sol = false;
-                break;  
+        // Cells of the words to regenerate, starting from the solution of the project
+        let mut regenerate_cells = None;
+        if let (Some(ids), Some(project)) = (&regenerate_ids, &project) {
+            let board_error = |reason: String| Error::BoardFormat { path: board_path.cloned().unwrap_or_default(), reason };
+            board = project.solution_board()?
+                .ok_or_else(|| board_error("the project has no solution to regenerate".to_owned()))?;
+
+            let mut cells = Vec::new();
+            for id in ids {
+                let word_pos = board.find_slot(id).ok_or_else(|| board_error(format!("no word with id '{}'", id)))?;
+                cells.extend((0..word_pos.len).map(|k| word_pos.cell(k)));
+            }
+            regenerate_cells = Some(cells);
+        }
+        let old_board = board.clone();
+
+        // Every word of an arrowword needs a clue cell
+        if board.has_clue_cells() {
+            if let Err(missing) = arrowword::arrow_slots(&board) {
+                let words: Vec<String> = missing.iter()
+                    .map(|wp| format!("{} at ({}, {})", output::direction_name(wp.dir), wp.x, wp.y))
+                    .collect();
+                return Err(Error::BoardFormat { path: board_path.cloned().unwrap_or_default(),
+                    reason: format!("words without a clue cell: {}", words.join(", ")) });
             }
         }
         
-        // continue recursively if there are intersecting words for each letter of the current word
-        if sol {
-            if !rep_words {
-                words_used.insert(current_word);
+        // Find solution
+        let options = GenerateOptions::new().shuffle(shuffle).seed(seed).repeat_words(rep_words)
+            .progress_interval(CLI_PROGRESS_INTERVAL).square(square);
+        let report = |progress: &Progress| {
+            if !json_output {
+                eprintln!("{}", progress.board.pretty());
+                eprintln!("Words: {}/{}, visited nodes: {} ({:.0} nodes/s), cached patterns: {}\n",
+                    progress.depth, progress.words, progress.visited_nodes, progress.nodes_per_sec, progress.cache_size);
             }
-
-            valid = fill_board(board, words_len, &words_pos[..words_pos.len() - 1], words_intersect,
-                                words_used, words_map_cache, visited_nodes, rep_words);
-            if valid {
-                break;
+            true
+        };
+        let sol = match &regenerate_cells {
+            Some(cells) => regenerate_with_progress(&mut board, cells, &words_len, &options, report)?,
+            None => generate_with_progress(&mut board, &words_len, &options, report)?,
+        };
+        let mut definitions = match sol.found {
+            true if board.has_clue_cells() => get_short_definitions(&board, words_def)?,
+            true => get_definitions(&board, words_def)?,
+            false => Vec::new(),
+        };
+        if let (Some(_), Some(project)) = (&regenerate_cells, &project) {
+            keep_definitions(&old_board, &project.definitions(), &board, &mut definitions);
+        }
+        let arrow_clues = arrowword::arrow_clues(&board, &definitions).unwrap_or_default();
+
+        // Save project
+        if let Some(path) = output_path.filter(|_| sol.found) {
+            let mut output = project.clone().unwrap_or_else(|| Project::new(board.width(), board.height()));
+            if project.is_none() {
+                output.template = board.rows().iter()
+                    .map(|r| r.chars().map(|c| if is_block(c) {c} else {' '}).collect())
+                    .collect();
             }
-            if !rep_words {
-                words_used.remove(current_word);
+            output.set_solution(&board, &definitions);
+            output.seed = seed;
+            output.dictionary = dictionary.clone();
+            output.translations = translations.clone();
+            output.reverse_translations = reverse_translations;
+            output.shuffle = shuffle;
+            output.repeat_words = rep_words;
+            output.save(path)?;
+            if !json_output {
+                println!("Project saved in: {}", path);
             }
         }
-    }
 
-    if !valid {
-        board.set_word(current_word_pos, current_word_board.as_str());
-    }
-
-    valid
-}
+        // Export SVG
+        if let Some(path) = svg_path.filter(|_| sol.found) {
+            export::save_svg(&board, &arrow_clues, false, path)?;
+            if !json_output {
+                println!("SVG saved in: {}", path);
+            }
+        }
 
+        // Export .puz and ipuz
+        if let Some(path) = puz_path.filter(|_| sol.found) {
+            export::save_puz(&board, &definitions, &puzzle_title(path), path)?;
+            if !json_output {
+                println!(".puz saved in: {}", path);
+            }
+        }
+        if let Some(path) = ipuz_path.filter(|_| sol.found) {
+            export::save_ipuz(&board, &definitions, &puzzle_title(path), path)?;
+            if !json_output {
+                println!("ipuz saved in: {}", path);
+            }
+        }
 
-fn get_valid_words<'a>(words: &'a [&str], word_board: &str) -> Vec<&'a str> {
-    words
-        .iter()
-        .filter(|&&word| is_valid(word_board, word))
-        .cloned()
-        .collect()
-}
+        // JSON
+        if json_output {
+            print_json(&output::JsonOutput {
+                version: VERSION,
+                settings: output::Settings {
+                    width: board.width(),
+                    height: board.height(),
+                    board: board_path.cloned(),
+                    dictionary,
+                    shuffle,
+                    seed,
+                    repeat_words: rep_words,
+                    regenerate: regenerate_ids.clone(),
+                },
+                grid: board.rows(),
+                entries: if sol.found { output::get_entries(&board, &definitions) } else { Vec::new() },
+                solution: &sol,
+                stats: GridStats::new(&board),
+                arrow_clues: arrow_clues.clone(),
+            })?;
+        }
 
+        // Solution found
+        else if sol.found {
+            println!("{}", board.pretty());
+            println!("Time to fill the board: {} ms", sol.time_elapsed);
+            print_definitions(definitions);
+            if !arrow_clues.is_empty() {
+                print_arrow_clues(&arrow_clues);
+            }
+        }
+        // Solution not found
+        else {
+            println!("No solution found in: {} ms", sol.time_elapsed);
+        }
+    
+        // Print Visited Nodes
+        if !json_output {
+            println!("\nSTATS");
+            println!("Visited nodes: {}", sol.visited_nodes);
+            println!("{}", GridStats::new(&board));
+        }
 
-fn is_valid(word_board: &str, word: &str) -> bool {
-    let mut word_chars = word.chars();
-    for c in word_board.chars() {
-        let c2 = word_chars.next().unwrap();
-        if c == ' ' {
-            continue;
+        if sol.found {
+            Ok(())
         }
-        if c != c2 {
-            return false;
+        else {
+            Err(Error::Solver(format!("No solution found for the {}x{} board", board.width(), board.height())))
         }
     }
-    true
+
+    // GUI
+    else {
+        let mut project = board_path.cloned().zip(project);
+        if let Some(mask) = &mask {
+            let mut board = Board::new(board_w, board_h);
+            mask.apply(&mut board);
+            let mut masked = Project::new(board_w, board_h);
+            masked.template = board.rows();
+            masked.dictionary = dictionary.clone();
+            masked.translations = translations.clone();
+            masked.reverse_translations = reverse_translations;
+            project = Some(("project.json".to_owned(), masked));
+        }
+        init_gui(VERSION, words_len, words_def, &dictionary, translations.map(|path| (path, reverse_translations)), project)
+    }
 }
 
 
-fn get_definitions(board: &Board, json: &serde_json::Value) -> Vec<(WordPos, String)> {
-    let mut list_defs: Vec<(WordPos, String)> = Vec::new();
-    let words_pos = board.get_words_pos();
+#[cfg(feature = "gui")]
+fn init_gui(ver: &str, words_len: WordIndex<'static>, definitions: &'static Dictionary,
+            dictionary: &str, translations: Option<(String, bool)>, project: Option<(String, Project)>) -> Result<(), Error> {
+    env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default().with_inner_size([1024.0, 640.0]),
+        ..Default::default()
+    };
 
-    for word_pos in words_pos {
-        let word = board.get_word(&word_pos);
-        let defs = json.get(word).unwrap().as_array().unwrap();
+    eframe::run_native(
+        format!("Crosswords Generator v{}", ver).as_str(),
+        options,
+        Box::new(|cc| {
+            egui_extras::install_image_loaders(&cc.egui_ctx); // support for images
+            //Ok(Box::<BaseApp>::default())
+            Ok(Box::new(BaseApp::new(&cc.egui_ctx, words_len, definitions, dictionary, translations, project)))
+        }),
+    ).map_err(|e| Error::Gui(e.to_string()))
+}
 
-        let random_index = rand::thread_rng().gen_range(0..defs.len());
-        let def_string = defs.get(random_index).unwrap().to_string();
 
-        list_defs.push((word_pos, def_string));
-    }
+#[cfg(not(feature = "gui"))]
+fn init_gui(_ver: &str, _words_len: WordIndex<'static>, _definitions: &'static Dictionary,
+            _dictionary: &str, _translations: Option<(String, bool)>, _project: Option<(String, Project)>) -> Result<(), Error> {
+    Err(Error::Gui("built without the 'gui' feature, use --no-gui".to_owned()))
+}
+
 
-    list_defs
+// Filled board of the project, for the puzzles made from a solution
+fn solution_board(project: Option<&Project>, board_path: Option<&String>) -> Result<Board, Error> {
+    let board_error = |reason: &str| Error::BoardFormat { path: board_path.cloned().unwrap_or_default(), reason: reason.to_owned() };
+    let project = project.ok_or_else(|| board_error("a project is required, see --board"))?;
+    project.solution_board()?.ok_or_else(|| board_error("the project has no solution"))
 }
 
+// Title of an exported puzzle: the name of its file
+fn puzzle_title(path: &str) -> String {
+    std::path::Path::new(path).file_stem().map_or(String::new(), |stem| stem.to_string_lossy().into_owned())
+}
+
+fn print_json<T: Serialize>(document: &T) -> Result<(), Error> {
+    println!("{}", output::to_json(document)?);
+    Ok(())
+}
 
 fn print_definitions(defs: Vec<(WordPos, String)>) {
     println!("\nDEFINITIONS");
@@ -353,3 +864,10 @@ fn print_definitions(defs: Vec<(WordPos, String)>) {
         println!("{:?}: {}", def.0, def.1);
     }
 }
+
+fn print_arrow_clues(clues: &[ArrowClue]) {
+    println!("\nCLUE CELLS");
+    for clue in clues {
+        println!("({}, {}) {} {}", clue.x, clue.y, clue.arrow.symbol(), clue.text);
+    }
+}
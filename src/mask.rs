@@ -0,0 +1,129 @@
+use std::fs;
+
+use crate::error::Error;
+use crate::utils::{Board, OUTSIDE_CELL};
+
+
+/// Shape of a non-rectangular board: the cells inside the puzzle, the others become outside cells
+///
+/// ```
+/// use crosswords_generator::mask::Mask;
+/// use crosswords_generator::Board;
+///
+/// let mask = Mask::from_text(".XX.\nXXXX\n.XX.");
+/// let mut board = Board::new(mask.width(), mask.height());
+/// mask.apply(&mut board);
+/// assert!(board.is_outside(0, 0));
+/// assert!(!board.is_outside(1, 0));
+/// assert_eq!(board.get_words_pos().len(), 5);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mask {
+    width: usize,
+    height: usize,
+    inside: Vec<bool>,
+}
+
+
+impl Mask {
+    /// Mask from text rows: '.', ' ' and '~' are outside, any other char is inside.
+    /// Shorter rows are completed with outside cells
+    pub fn from_text(text: &str) -> Mask {
+        let mut rows: Vec<Vec<bool>> = text.lines()
+            .map(|row| row.trim_end_matches('\r').chars().map(|c| !matches!(c, '.' | ' ' | OUTSIDE_CELL)).collect())
+            .collect();
+        while rows.last().is_some_and(|row| !row.contains(&true)) {
+            rows.pop();
+        }
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let height = rows.len();
+        for row in rows.iter_mut() {
+            row.resize(width, false);
+        }
+        Mask { width, height, inside: rows.concat() }
+    }
+
+    /// Elliptic mask filling the board, a circle on square boards
+    pub fn ellipse(width: usize, height: usize) -> Mask {
+        let inside = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let dx = (x as f64 + 0.5) * 2.0 / width as f64 - 1.0;
+                let dy = (y as f64 + 0.5) * 2.0 / height as f64 - 1.0;
+                dx * dx + dy * dy <= 1.0
+            })
+            .collect();
+        Mask { width, height, inside }
+    }
+
+    /// Mask from an image scaled to the size of the board: a cell is inside when the pixel
+    /// at its centre is dark and not transparent
+    #[cfg(feature = "image")]
+    pub fn from_image(path: &str, width: usize, height: usize) -> Result<Mask, Error> {
+        let image = image::open(path)
+            .map_err(|e| Error::BoardFormat { path: path.to_owned(), reason: e.to_string() })?
+            .to_luma_alpha8();
+        let (w, h) = image.dimensions();
+        let inside = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let px = ((x as f64 + 0.5) * w as f64 / width as f64) as u32;
+                let py = ((y as f64 + 0.5) * h as f64 / height as f64) as u32;
+                let [luma, alpha] = image.get_pixel(px.min(w - 1), py.min(h - 1)).0;
+                luma < 128 && alpha >= 128
+            })
+            .collect();
+        Ok(Mask { width, height, inside })
+    }
+
+    #[cfg(not(feature = "image"))]
+    pub fn from_image(path: &str, _width: usize, _height: usize) -> Result<Mask, Error> {
+        Err(Error::BoardFormat { path: path.to_owned(), reason: "built without the 'image' feature".to_owned() })
+    }
+
+    /// Load a mask from a text file or, with the `image` feature, from a PNG image scaled
+    /// to the given size. A text mask has the size of its rows
+    pub fn load(path: &str, width: usize, height: usize) -> Result<Mask, Error> {
+        let board_error = |reason: String| Error::BoardFormat { path: path.to_owned(), reason };
+
+        let mask = if path.to_lowercase().ends_with(".png") {
+            Mask::from_image(path, width, height)?
+        }
+        else {
+            Mask::from_text(&fs::read_to_string(path).map_err(|e| board_error(e.to_string()))?)
+        };
+
+        if !mask.inside.contains(&true) {
+            return Err(board_error("the mask has no cells inside the shape".to_owned()));
+        }
+        Ok(mask)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Check if the cell is inside the shape, coordinates outside the mask are not
+    pub fn is_inside(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.inside[y * self.width + x]
+    }
+
+    /// Turn the cells outside the shape into outside cells, the cells inside the shape
+    /// that were outside become empty. The other cells are kept
+    pub fn apply(&self, board: &mut Board) {
+        for y in 0..board.height() {
+            for x in 0..board.width() {
+                if !self.is_inside(x, y) {
+                    let _ = board.set(x, y, OUTSIDE_CELL);
+                }
+                else if board.is_outside(x, y) {
+                    let _ = board.set(x, y, ' ');
+                }
+            }
+        }
+    }
+}
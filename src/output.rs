@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::arrowword::ArrowClue;
+use crate::error::Error;
+use crate::stats::GridStats;
+use crate::utils::{Board, Dir, Solution, WordPos};
+
+
+#[derive(Debug, Serialize)]
+pub struct Settings {
+    pub width: usize,
+    pub height: usize,
+    pub board: Option<String>,
+    pub dictionary: String,
+    pub shuffle: bool,
+    pub seed: u64,
+    pub repeat_words: bool,
+    /// ids of the regenerated words, if only some words were regenerated
+    pub regenerate: Option<Vec<String>>,
+}
+
+
+#[derive(Debug, Serialize)]
+pub struct Entry {
+    pub number: usize,
+    pub direction: &'static str,
+    pub x: usize,
+    pub y: usize,
+    pub len: usize,
+    pub answer: String,
+    pub clue: Option<String>,
+}
+
+
+/// Document printed by the CLI with `--format json`
+#[derive(Debug, Serialize)]
+pub struct JsonOutput<'a> {
+    pub version: &'a str,
+    pub settings: Settings,
+    pub grid: Vec<String>,
+    pub entries: Vec<Entry>,
+    pub solution: &'a Solution,
+    pub stats: GridStats,
+    /// clues placed in the clue cells of an arrowword
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub arrow_clues: Vec<ArrowClue>,
+}
+
+
+/// Document printed by the `crisscross` subcommand with `--format json`
+#[derive(Debug, Serialize)]
+pub struct CrissCrossOutput<'a> {
+    pub version: &'a str,
+    pub seed: u64,
+    pub grid: Vec<String>,
+    pub entries: Vec<Entry>,
+    pub intersections: usize,
+    /// words that could not be placed in the grid
+    pub unplaced: Vec<String>,
+    pub stats: GridStats,
+}
+
+
+/// Document printed by the `fillin` subcommand with `--format json`
+#[derive(Debug, Serialize)]
+pub struct FillInOutput<'a> {
+    pub version: &'a str,
+    /// rows of the grid with the starter letters
+    pub grid: Vec<String>,
+    /// words grouped by length
+    pub words: &'a BTreeMap<usize, Vec<String>>,
+    pub starters: &'a [(usize, usize)],
+    pub unique: bool,
+}
+
+
+/// Document printed by the `codeword` subcommand with `--format json`
+#[derive(Debug, Serialize)]
+pub struct CodewordOutput<'a> {
+    pub version: &'a str,
+    pub seed: u64,
+    /// number of each cell, null for blocks
+    pub grid: Vec<Vec<Option<usize>>>,
+    pub cipher: &'a BTreeMap<char, usize>,
+    pub revealed: &'a [char],
+    pub unique: bool,
+}
+
+
+/// Document printed by the `diagramless` subcommand with `--format json`
+#[derive(Debug, Serialize)]
+pub struct DiagramlessOutput<'a> {
+    pub version: &'a str,
+    pub width: usize,
+    pub height: usize,
+    /// numbered entries with their starting square, answer and clue
+    pub entries: Vec<Entry>,
+    /// rows of the filled board with the black cells
+    pub solution: Vec<String>,
+}
+
+
+pub fn direction_name(dir: Dir) -> &'static str {
+    match dir {
+        Dir::HOR => "across",
+        Dir::VER => "down",
+    }
+}
+
+
+/// Numbered entries of the board with their answer and clue (if any)
+pub fn get_entries(board: &Board, definitions: &[(WordPos, String)]) -> Vec<Entry> {
+    board.get_numbered_words_pos().into_iter()
+        .map(|(number, word_pos)| Entry {
+            number,
+            direction: direction_name(word_pos.dir),
+            x: word_pos.x,
+            y: word_pos.y,
+            len: word_pos.len,
+            answer: board.get_word(&word_pos),
+            clue: definitions.iter().find(|(wp, _)| *wp == word_pos).map(|(_, def)| def.clone()),
+        })
+        .collect()
+}
+
+
+/// Document as pretty printed JSON, printed by the CLI with `--format json`
+pub fn to_json<T: Serialize>(output: &T) -> Result<String, Error> {
+    serde_json::to_string_pretty(output)
+        .map_err(|e| Error::Export { path: "stdout".to_owned(), reason: e.to_string() })
+}
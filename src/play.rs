@@ -0,0 +1,131 @@
+use crate::error::Error;
+use crate::utils::{is_block, Board};
+
+
+/// Solving state of a filled board: letters typed by the player, wrong and revealed cells
+///
+/// ```
+/// use crosswords_generator::{Board, Play};
+///
+/// let mut play = Play::new(Board::from_rows(&["AB".to_owned(), "CD".to_owned()]));
+/// play.set(0, 0, 'A').unwrap();
+/// play.set(1, 0, 'X').unwrap();
+/// assert_eq!(play.check(&play.cells()).unwrap(), 1);
+/// assert!(play.is_wrong(1, 0));
+///
+/// play.reveal(&play.cells()).unwrap();
+/// assert!(play.is_solved());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Play {
+    solution: Board,
+    entries: Board,
+    wrong: Vec<bool>,
+    revealed: Vec<bool>,
+}
+
+
+impl Play {
+    /// Start solving a filled board, the player sees only the black cells
+    pub fn new(solution: Board) -> Play {
+        let mut entries = solution.clone();
+        for y in 0..solution.height() {
+            for x in 0..solution.width() {
+                if entries.valid(x, y) {
+                    let _ = entries.set(x, y, ' ');
+                }
+            }
+        }
+        let cells = solution.width() * solution.height();
+        Play {
+            entries,
+            solution,
+            wrong: vec![false; cells],
+            revealed: vec![false; cells],
+        }
+    }
+
+    pub fn solution(&self) -> &Board {
+        &self.solution
+    }
+
+    /// Board with the letters typed by the player
+    pub fn entries(&self) -> &Board {
+        &self.entries
+    }
+
+    fn idx(&self, x: usize, y: usize) -> usize {
+        self.solution.width() * y + x
+    }
+
+    /// Letter typed by the player in the cell
+    pub fn get(&self, x: usize, y: usize) -> Result<char, Error> {
+        self.entries.get(x, y)
+    }
+
+    /// Write a letter in the cell, ' ' clears it. Black and revealed cells are not changed
+    pub fn set(&mut self, x: usize, y: usize, c: char) -> Result<(), Error> {
+        let idx = self.idx(x, y);
+        if is_block(self.entries.get(x, y)?) || self.revealed[idx] {
+            return Ok(());
+        }
+        self.entries.set(x, y, c)?;
+        self.wrong[idx] = false;
+        Ok(())
+    }
+
+    /// The letter of the cell was checked and it is wrong
+    pub fn is_wrong(&self, x: usize, y: usize) -> bool {
+        self.wrong.get(self.idx(x, y)).copied().unwrap_or(false)
+    }
+
+    pub fn is_revealed(&self, x: usize, y: usize) -> bool {
+        self.revealed.get(self.idx(x, y)).copied().unwrap_or(false)
+    }
+
+    /// Open cells of the board
+    pub fn cells(&self) -> Vec<(usize, usize)> {
+        (0..self.solution.height())
+            .flat_map(|y| (0..self.solution.width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.solution.get(x, y).is_ok_and(|c| !is_block(c)))
+            .collect()
+    }
+
+    /// Mark the wrong letters among the given cells, empty cells are not wrong.
+    /// Rebus cells are checked on their first letter.
+    /// Returns the number of wrong letters
+    pub fn check(&mut self, cells: &[(usize, usize)]) -> Result<usize, Error> {
+        let mut wrong = 0;
+        for &(x, y) in cells {
+            let entry = self.entries.get(x, y)?;
+            if entry != ' ' && entry != self.solution.get(x, y)? {
+                let idx = self.idx(x, y);
+                self.wrong[idx] = true;
+                wrong += 1;
+            }
+        }
+        Ok(wrong)
+    }
+
+    /// Show the solution in the given cells
+    pub fn reveal(&mut self, cells: &[(usize, usize)]) -> Result<(), Error> {
+        for &(x, y) in cells {
+            let c = self.solution.get(x, y)?;
+            if !is_block(c) {
+                self.entries.set(x, y, c)?;
+                if let Some(letters) = self.solution.rebus(x, y) {
+                    self.entries.set_rebus(x, y, letters)?;
+                }
+                let idx = self.idx(x, y);
+                self.wrong[idx] = false;
+                self.revealed[idx] = true;
+            }
+        }
+        Ok(())
+    }
+
+    /// Every cell holds the letter of the solution, the first letter is enough for rebus cells
+    pub fn is_solved(&self) -> bool {
+        self.entries.rows() == self.solution.rows()
+    }
+}
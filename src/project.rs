@@ -0,0 +1,160 @@
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::utils::{is_block, Board, WordPos};
+
+pub const PROJECT_VERSION: u32 = 1;
+pub const DEFAULT_DICTIONARY: &str = "./data/words.txt";
+
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Clue {
+    pub pos: WordPos,
+    pub text: String,
+}
+
+
+/// Cell holding several letters
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rebus {
+    pub x: usize,
+    pub y: usize,
+    pub text: String,
+}
+
+
+/// Puzzle project saved on disk: everything needed to reopen or regenerate a puzzle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Project {
+    pub version: u32,
+    pub width: usize,
+    pub height: usize,
+    /// rows of the template, '#' for black cells, '@' for clue cells, '~' for cells outside
+    /// the shape of the board and ' ' for open cells
+    pub template: Vec<String>,
+    /// rows of letters fixed by the user before filling, ' ' elsewhere
+    pub locked: Vec<String>,
+    /// rows of the bars of a barred grid, see [`Board::bar_rows`], empty without bars
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bars: Vec<String>,
+    /// rebus cells set by the user, they are kept while filling
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rebus: Vec<Rebus>,
+    /// rows of the filled board, if a solution was found
+    pub solution: Option<Vec<String>>,
+    pub clues: Vec<Clue>,
+    pub seed: u64,
+    pub dictionary: String,
+    /// translations giving the clues of a bilingual crossword, see [`crate::Translations`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translations: Option<String>,
+    /// the translations are used in the other direction: the answers are the translated words
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub reverse_translations: bool,
+    pub shuffle: bool,
+    pub repeat_words: bool,
+}
+
+
+impl Project {
+    pub fn new(width: usize, height: usize) -> Project {
+        Project {
+            version: PROJECT_VERSION,
+            width,
+            height,
+            template: vec![" ".repeat(width); height],
+            locked: vec![" ".repeat(width); height],
+            bars: Vec::new(),
+            rebus: Vec::new(),
+            solution: None,
+            clues: Vec::new(),
+            seed: 0,
+            dictionary: DEFAULT_DICTIONARY.to_owned(),
+            translations: None,
+            reverse_translations: false,
+            shuffle: false,
+            repeat_words: false,
+        }
+    }
+
+    /// Read a project from a json file
+    pub fn load(path: &str) -> Result<Project, Error> {
+        let board_error = |reason: String| Error::BoardFormat { path: path.to_owned(), reason };
+
+        let content = fs::read_to_string(path).map_err(|e| board_error(e.to_string()))?;
+        let project: Project = serde_json::from_str(content.as_str()).map_err(|e| board_error(e.to_string()))?;
+
+        if project.template.len() != project.height || project.locked.len() != project.height
+            || project.template.iter().chain(project.locked.iter()).any(|r| r.chars().count() != project.width) {
+            return Err(board_error(format!("rows do not match the size {}x{}", project.width, project.height)));
+        }
+        if !project.bars.is_empty()
+            && (project.bars.len() != project.height || project.bars.iter().any(|r| r.chars().count() != project.width)) {
+            return Err(board_error(format!("bars do not match the size {}x{}", project.width, project.height)));
+        }
+        if let Some(solution) = &project.solution {
+            if solution.len() != project.height || solution.iter().any(|r| r.chars().count() != project.width) {
+                return Err(board_error(format!("solution does not match the size {}x{}", project.width, project.height)));
+            }
+        }
+        if let Some(rebus) = project.rebus.iter().find(|r| r.x >= project.width || r.y >= project.height || r.text.is_empty()) {
+            return Err(board_error(format!("invalid rebus cell at ({}, {})", rebus.x, rebus.y)));
+        }
+        Ok(project)
+    }
+
+    /// Write the project as a json file
+    pub fn save(&self, path: &str) -> Result<(), Error> {
+        let export_error = |reason: String| Error::Export { path: path.to_owned(), reason };
+
+        let content = serde_json::to_string_pretty(self).map_err(|e| export_error(e.to_string()))?;
+        fs::write(path, content).map_err(|e| export_error(e.to_string()))
+    }
+
+    /// Board to fill: black cells from the template, the locked letters and the rebus cells
+    pub fn board(&self) -> Result<Board, Error> {
+        let mut board = Board::from_rows(&self.template);
+        board.set_bar_rows(&self.bars);
+        for (j, row) in self.locked.iter().enumerate() {
+            for (i, c) in row.chars().enumerate() {
+                if c != ' ' && !is_block(board.get(i, j)?) {
+                    board.set(i, j, c)?;
+                }
+            }
+        }
+        self.set_rebus(&mut board)?;
+        Ok(board)
+    }
+
+    /// Filled board with the bars and the rebus cells, if a solution was found
+    pub fn solution_board(&self) -> Result<Option<Board>, Error> {
+        let Some(solution) = &self.solution else {
+            return Ok(None);
+        };
+        let mut board = Board::from_rows(solution);
+        board.set_bar_rows(&self.bars);
+        self.set_rebus(&mut board)?;
+        Ok(Some(board))
+    }
+
+    fn set_rebus(&self, board: &mut Board) -> Result<(), Error> {
+        for rebus in &self.rebus {
+            board.set_rebus(rebus.x, rebus.y, &rebus.text)?;
+        }
+        Ok(())
+    }
+
+    /// Store the filled board and its definitions
+    pub fn set_solution(&mut self, board: &Board, definitions: &[(WordPos, String)]) {
+        self.solution = Some(board.rows());
+        self.clues = definitions.iter()
+            .map(|(pos, text)| Clue { pos: *pos, text: text.clone() })
+            .collect();
+    }
+
+    pub fn definitions(&self) -> Vec<(WordPos, String)> {
+        self.clues.iter().map(|c| (c.pos, c.text.clone())).collect()
+    }
+}
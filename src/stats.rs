@@ -0,0 +1,117 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::utils::{is_block, Board, OUTSIDE_CELL};
+
+
+/// Metrics of a template and of its fill
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GridStats {
+    pub words: usize,
+    pub average_length: f64,
+    /// number of words for each length
+    pub lengths: BTreeMap<usize, usize>,
+    /// black and clue cells, the cells outside the shape of the board are not counted
+    pub black_cells: usize,
+    pub black_percentage: f64,
+    /// open cells belonging to a single word (or to none)
+    pub unchecked_cells: usize,
+    /// occurrences of each letter on the board
+    pub letters: BTreeMap<char, usize>,
+    /// sum of the Scrabble values of the letters on the board
+    pub scrabble_score: u32,
+    /// complete words appearing more than once on the board
+    pub repeated_words: usize,
+}
+
+
+impl GridStats {
+    pub fn new(board: &Board) -> GridStats {
+        let words_pos = board.get_words_pos();
+        // the cells outside the shape of the board do not count
+        let cells = (0..board.height())
+            .flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| !board.is_outside(x, y))
+            .count();
+
+        let mut lengths: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut checks: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut answers: HashMap<String, usize> = HashMap::new();
+        for word_pos in &words_pos {
+            *lengths.entry(word_pos.len).or_default() += 1;
+            for k in 0..word_pos.len {
+                *checks.entry(word_pos.cell(k)).or_default() += 1;
+            }
+            let word = board.get_word(word_pos);
+            if !word.contains(' ') {
+                *answers.entry(word).or_default() += 1;
+            }
+        }
+
+        let mut black_cells = 0;
+        let mut unchecked_cells = 0;
+        let mut letters: BTreeMap<char, usize> = BTreeMap::new();
+        for y in 0..board.height() {
+            for x in 0..board.width() {
+                match board.get(x, y).unwrap_or('#') {
+                    OUTSIDE_CELL => (),
+                    c if is_block(c) => black_cells += 1,
+                    c => {
+                        if checks.get(&(x, y)).copied().unwrap_or(0) < 2 {
+                            unchecked_cells += 1;
+                        }
+                        if c != ' ' {
+                            *letters.entry(c).or_default() += 1;
+                        }
+                    },
+                }
+            }
+        }
+
+        GridStats {
+            words: words_pos.len(),
+            average_length: if words_pos.is_empty() { 0.0 }
+                else { words_pos.iter().map(|wp| wp.len).sum::<usize>() as f64 / words_pos.len() as f64 },
+            lengths,
+            black_cells,
+            black_percentage: if cells == 0 { 0.0 } else { black_cells as f64 * 100.0 / cells as f64 },
+            unchecked_cells,
+            scrabble_score: letters.iter().map(|(&c, &n)| scrabble_value(c) * n as u32).sum(),
+            letters,
+            repeated_words: answers.values().filter(|&&n| n > 1).count(),
+        }
+    }
+}
+
+
+/// The statistics as text, one line each
+impl fmt::Display for GridStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lengths: Vec<String> = self.lengths.iter().map(|(len, n)| format!("{}: {}", len, n)).collect();
+        let letters: Vec<String> = self.letters.iter().map(|(c, n)| format!("{}: {}", c, n)).collect();
+        writeln!(f, "Words: {} (average length {:.2})", self.words, self.average_length)?;
+        writeln!(f, "Lengths: {}", lengths.join(", "))?;
+        writeln!(f, "Black cells: {} ({:.1}%)", self.black_cells, self.black_percentage)?;
+        writeln!(f, "Unchecked cells: {}", self.unchecked_cells)?;
+        writeln!(f, "Letters: {}", letters.join(", "))?;
+        writeln!(f, "Scrabble score: {}", self.scrabble_score)?;
+        write!(f, "Repeated words: {}", self.repeated_words)
+    }
+}
+
+
+/// Value of a letter in English Scrabble, 0 for other characters
+pub fn scrabble_value(c: char) -> u32 {
+    match c.to_ascii_uppercase() {
+        'A' | 'E' | 'I' | 'O' | 'U' | 'L' | 'N' | 'S' | 'T' | 'R' => 1,
+        'D' | 'G' => 2,
+        'B' | 'C' | 'M' | 'P' => 3,
+        'F' | 'H' | 'V' | 'W' | 'Y' => 4,
+        'K' => 5,
+        'J' | 'X' => 8,
+        'Q' | 'Z' => 10,
+        _ => 0,
+    }
+}
@@ -1,18 +1,101 @@
+use std::collections::BTreeMap;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+/// Clue cell of an arrowword: a block holding the clues of the words next to it
+pub const CLUE_CELL: char = '@';
+
+/// Cell outside the shape of a non-rectangular board, it is not drawn
+pub const OUTSIDE_CELL: char = '~';
+
+/// Check if the cell content ends the words: black cell, clue cell or cell outside the board shape
+pub fn is_block(c: char) -> bool {
+    c == '#' || c == CLUE_CELL || c == OUTSIDE_CELL
+}
+
+/// Direction of a word: horizontal (across) or vertical (down)
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Dir {
     HOR,
     VER,
 }
 
-#[derive(Debug)]
+impl Dir {
+    /// The crossing direction
+    pub fn other(self) -> Dir {
+        match self {
+            Dir::HOR => Dir::VER,
+            Dir::VER => Dir::HOR,
+        }
+    }
+}
+
+/// Symmetry of the black cells of a board
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Symmetry {
+    #[default]
+    None,
+    /// 180° rotation around the center
+    Rotational,
+    /// mirror from left to right: (x, y) -> (w - 1 - x, y)
+    Horizontal,
+    /// mirror from top to bottom: (x, y) -> (x, h - 1 - y)
+    Vertical,
+    /// both mirrors, which implies the rotation
+    FourWay,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 5] = [Symmetry::None, Symmetry::Rotational, Symmetry::Horizontal,
+                                    Symmetry::Vertical, Symmetry::FourWay];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Symmetry::None => "None",
+            Symmetry::Rotational => "180° Rotational",
+            Symmetry::Horizontal => "Horizontal Mirror",
+            Symmetry::Vertical => "Vertical Mirror",
+            Symmetry::FourWay => "Four-way",
+        }
+    }
+
+    /// Cells symmetric to (x, y) on a w x h board, (x, y) included and without duplicates
+    pub fn partners(&self, x: usize, y: usize, w: usize, h: usize) -> Vec<(usize, usize)> {
+        // no cells on empty boards, nor outside the board
+        if x >= w || y >= h {
+            return Vec::new();
+        }
+        let (mx, my) = (w - 1 - x, h - 1 - y);
+        let mut cells = match self {
+            Symmetry::None => vec![(x, y)],
+            Symmetry::Rotational => vec![(x, y), (mx, my)],
+            Symmetry::Horizontal => vec![(x, y), (mx, y)],
+            Symmetry::Vertical => vec![(x, y), (x, my)],
+            Symmetry::FourWay => vec![(x, y), (mx, y), (x, my), (mx, my)],
+        };
+        cells.sort_unstable();
+        cells.dedup();
+        cells
+    }
+}
+
+/// Outcome of a search: whether the board was filled, time in ms and visited nodes
+#[derive(Debug, Serialize)]
 pub struct Solution {
     pub found: bool,
+    /// the search was stopped before the end
+    pub cancelled: bool,
     pub time_elapsed: u128,
     pub visited_nodes: u64,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// Position of a word on the board: starting cell, direction and length
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct WordPos {
     pub x: usize,
     pub y: usize,
@@ -29,13 +112,38 @@ impl WordPos {
             len,
         }
     }
+
+    /// Cell of the k-th letter of the word
+    pub fn cell(&self, k: usize) -> (usize, usize) {
+        match self.dir {
+            Dir::HOR => (self.x + k, self.y),
+            Dir::VER => (self.x, self.y + k),
+        }
+    }
+
+    /// Index of the letter of the word in the cell (x, y), if the word covers the cell
+    pub fn index_of(&self, x: usize, y: usize) -> Option<usize> {
+        let (fixed, start, pos, other) = match self.dir {
+            Dir::HOR => (self.y, self.x, x, y),
+            Dir::VER => (self.x, self.y, y, x),
+        };
+        (other == fixed && pos >= start && pos < start + self.len).then(|| pos - start)
+    }
 }
 
-#[derive(Debug, Clone)]
+/// Grid of cells: '#' for black cells, '@' for clue cells (arrowwords), '~' for cells outside the shape
+/// of the board, ' ' for empty cells, letters otherwise.
+/// Words are split by black cells and by the bars between cells (barred grids).
+/// Rebus cells hold a string of letters, their first letter is the char of the cell
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Board {
     width: usize,
     height: usize,
     arr: Vec<char>,
+    /// for each cell, bar after the cell in the HOR (right edge) and VER (bottom edge) direction
+    bars: Vec<[bool; 2]>,
+    /// letters of the rebus cells by cell index
+    rebus: BTreeMap<usize, String>,
 }
 
 impl Board {
@@ -44,61 +152,299 @@ impl Board {
             width: w,
             height: h,
             arr: vec![' '; w * h],
+            bars: vec![[false; 2]; w * h],
+            rebus: BTreeMap::new(),
+        }
+    }
+
+    /// Create a board with random black cells covering about `density` of the cells,
+    /// optionally with 180° rotational symmetry
+    pub fn random_pattern<R: Rng>(w: usize, h: usize, density: f64, symmetric: bool, rng: &mut R) -> Board {
+        let mut board = Board::new(w, h);
+        let target = (density * (w * h) as f64).round() as usize;
+
+        let mut cells: Vec<(usize, usize)> = (0..h).flat_map(|j| (0..w).map(move |i| (i, j))).collect();
+        cells.shuffle(rng);
+
+        let mut blacks = 0;
+        for (i, j) in cells {
+            if blacks >= target {
+                break;
+            }
+            if is_block(board.arr[board.idx(i, j)]) {
+                continue;
+            }
+
+            let symmetry = if symmetric {Symmetry::Rotational} else {Symmetry::None};
+            let changed = symmetry.partners(i, j, w, h);
+            for &(x, y) in &changed {
+                let idx = board.idx(x, y);
+                board.arr[idx] = '#';
+            }
+
+            // revert if some cell is not part of any word anymore
+            if board.has_isolated_cells() {
+                for &(x, y) in &changed {
+                    let idx = board.idx(x, y);
+                    board.arr[idx] = ' ';
+                }
+            }
+            else {
+                blacks += changed.len();
+            }
+        }
+        board
+    }
+
+    /// Create a board from its rows, each row is a string of the same length
+    pub fn from_rows(rows: &[String]) -> Board {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |r| r.chars().count());
+        let mut board = Board::new(width, height);
+        for (j, row) in rows.iter().enumerate() {
+            for (i, c) in row.chars().take(width).enumerate() {
+                let idx = board.idx(i, j);
+                board.arr[idx] = c;
+            }
         }
+        board
+    }
+
+    /// Get the rows of the board as strings
+    pub fn rows(&self) -> Vec<String> {
+        self.arr.chunks(self.width.max(1)).map(|row| row.iter().collect()).collect()
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
     }
 
-    // Get char at given coordinate
-    pub fn get(&self, x: usize, y: usize) -> char {
-        *self.arr.get(self.width * y + x).unwrap()
+    pub fn height(&self) -> usize {
+        self.height
     }
 
-    // Get word at given coordinate with dir and len
+    /// Index in arr of the given coordinate, the coordinate must be inside the board
+    fn idx(&self, x: usize, y: usize) -> usize {
+        self.width * y + x
+    }
+
+    fn check_bounds(&self, x: usize, y: usize) -> Result<(), Error> {
+        if x < self.width && y < self.height {
+            Ok(())
+        }
+        else {
+            Err(Error::OutOfBounds { x, y, width: self.width, height: self.height })
+        }
+    }
+
+    /// Get char at given coordinate
+    pub fn get(&self, x: usize, y: usize) -> Result<char, Error> {
+        self.check_bounds(x, y)?;
+        Ok(self.arr[self.idx(x, y)])
+    }
+
+    /// Get word at given coordinate with dir and len, rebus cells give all their letters
     pub fn get_word(&self, word_pos: &WordPos) -> String {
         let mut word = String::new();
-        match word_pos.dir {
-            Dir::HOR => {
-                for i in 0..word_pos.len {
-                    word.push(*self.arr.get(self.width * word_pos.y + word_pos.x + i).unwrap());
-                }
-            },
-            Dir::VER => {
-                for i in 0..word_pos.len {
-                    word.push(*self.arr.get(self.width * (word_pos.y + i) + word_pos.x).unwrap());
-                }
-            },
+        for k in 0..word_pos.len {
+            let (x, y) = word_pos.cell(k);
+            let idx = self.idx(x, y);
+            match self.rebus.get(&idx) {
+                Some(letters) => word.push_str(letters),
+                None => word.push(self.arr[idx]),
+            }
         }
         word
     }
 
-    // Set char at given coordinate
-    #[allow(dead_code)]
-    pub fn set(&mut self, x: usize, y: usize, val: char) {
-        self.arr[self.width * y + x] = val;
+    /// Number of letters of the word, more than its cells if it crosses rebus cells
+    pub fn word_len(&self, word_pos: &WordPos) -> usize {
+        self.char_index(word_pos, word_pos.len)
+    }
+
+    /// Position in the word letters of the k-th cell of the word
+    pub fn char_index(&self, word_pos: &WordPos, k: usize) -> usize {
+        (0..k).map(|i| {
+            let (x, y) = word_pos.cell(i);
+            self.rebus.get(&self.idx(x, y)).map_or(1, |letters| letters.chars().count())
+        }).sum()
+    }
+
+    /// Set char at given coordinate, a rebus cell becomes a normal cell
+    pub fn set(&mut self, x: usize, y: usize, val: char) -> Result<(), Error> {
+        self.check_bounds(x, y)?;
+        let idx = self.idx(x, y);
+        self.arr[idx] = val;
+        self.rebus.remove(&idx);
+        Ok(())
     }
 
-    // Set word at given coordinate with dir and len
+    /// Set word at given coordinate with dir and len. A rebus cell takes as many letters
+    /// of the word as it holds, so a rebus of blanks is filled like the empty cells
     pub fn set_word(&mut self, word_pos: &WordPos, word: &str) {
-        let mut word_chars = word.chars();
-        match word_pos.dir {
-            Dir::HOR => {
-                for i in 0..word_pos.len {
-                    self.arr[self.width * word_pos.y + word_pos.x + i] = word_chars.next().unwrap();
-                }
+        let mut chars = word.chars();
+        for k in 0..word_pos.len {
+            let (x, y) = word_pos.cell(k);
+            let idx = self.idx(x, y);
+            match self.rebus.get_mut(&idx) {
+                Some(letters) => {
+                    let len = letters.chars().count();
+                    let new: String = chars.by_ref().take(len).collect();
+                    if new.chars().count() < len {
+                        break;
+                    }
+                    self.arr[idx] = new.chars().next().unwrap_or(' ');
+                    *letters = new;
+                },
+                None => match chars.next() {
+                    Some(c) => self.arr[idx] = c,
+                    None => break,
+                },
+            }
+        }
+    }
+
+    /// Make the cell a rebus cell holding the given letters, with an empty string it becomes a normal cell
+    ///
+    /// ```
+    /// use crosswords_generator::{Board, Dir, WordPos};
+    ///
+    /// let mut board = Board::from_rows(&["C T".to_owned()]);
+    /// board.set_rebus(1, 0, "AR").unwrap();
+    /// let word_pos = WordPos::new(0, 0, Dir::HOR, 3);
+    /// assert_eq!(board.word_len(&word_pos), 4);
+    ///
+    /// board.set_word(&word_pos, "CART");
+    /// assert_eq!(board.get_word(&word_pos), "CART");
+    ///
+    /// board.set_rebus(1, 0, "  ").unwrap();
+    /// board.set_word(&word_pos, "COAT");
+    /// assert_eq!(board.rebus(1, 0), Some("OA"));
+    /// ```
+    pub fn set_rebus(&mut self, x: usize, y: usize, letters: &str) -> Result<(), Error> {
+        self.check_bounds(x, y)?;
+        let idx = self.idx(x, y);
+        match letters.chars().next() {
+            Some(first) => {
+                self.arr[idx] = first;
+                self.rebus.insert(idx, letters.to_owned());
             },
-            Dir::VER => {
-                for i in 0..word_pos.len {
-                    self.arr[self.width * (word_pos.y + i) + word_pos.x] = word_chars.next().unwrap();
-                }
+            None => {
+                self.rebus.remove(&idx);
             },
         }
+        Ok(())
+    }
+
+    /// Letters of the cell, if it is a rebus cell
+    pub fn rebus(&self, x: usize, y: usize) -> Option<&str> {
+        if x < self.width && y < self.height {
+            self.rebus.get(&self.idx(x, y)).map(String::as_str)
+        }
+        else {
+            None
+        }
+    }
+
+    /// Rebus cells (x, y, letters) in reading order
+    pub fn rebus_cells(&self) -> Vec<(usize, usize, &str)> {
+        self.rebus.iter().map(|(&idx, letters)| (idx % self.width, idx / self.width, letters.as_str())).collect()
+    }
+
+    pub fn has_rebus(&self) -> bool {
+        !self.rebus.is_empty()
     }
 
-    // Check if cell at the given coordinate is valid (not out of bounds and cell != '#')
+    /// Check if cell at the given coordinate is valid (not out of bounds and not a block, see [`is_block`])
     pub fn valid(&self, x: usize, y: usize) -> bool {
-        x < self.width && y < self.height && self.get(x, y) != '#'
+        x < self.width && y < self.height && !is_block(self.arr[self.idx(x, y)])
+    }
+
+    /// Check if the board has clue cells, so it is an arrowword
+    pub fn has_clue_cells(&self) -> bool {
+        self.arr.contains(&CLUE_CELL)
+    }
+
+    /// Check if the board has cells outside its shape, so it is not a rectangle
+    pub fn has_outside_cells(&self) -> bool {
+        self.arr.contains(&OUTSIDE_CELL)
+    }
+
+    /// Check if the cell is outside the shape of the board, coordinates outside the board are too
+    pub fn is_outside(&self, x: usize, y: usize) -> bool {
+        !self.get(x, y).is_ok_and(|c| c != OUTSIDE_CELL)
+    }
+
+    /// Check if there is a bar after the cell (x, y) in the given direction:
+    /// on the right edge for HOR, on the bottom edge for VER
+    pub fn has_bar(&self, x: usize, y: usize, dir: Dir) -> bool {
+        x < self.width && y < self.height && self.bars[self.idx(x, y)][dir as usize]
+    }
+
+    /// Set or remove the bar after the cell (x, y) in the given direction
+    pub fn set_bar(&mut self, x: usize, y: usize, dir: Dir, bar: bool) -> Result<(), Error> {
+        self.check_bounds(x, y)?;
+        let idx = self.idx(x, y);
+        self.bars[idx][dir as usize] = bar;
+        Ok(())
+    }
+
+    pub fn has_bars(&self) -> bool {
+        self.bars.iter().any(|b| b[0] || b[1])
+    }
+
+    /// Get the bars as rows of strings: '|' for a bar on the right of the cell,
+    /// '_' for a bar below the cell, '+' for both and ' ' for none
+    pub fn bar_rows(&self) -> Vec<String> {
+        self.bars.chunks(self.width.max(1))
+            .map(|row| row.iter().map(|b| match b {
+                [true, true] => '+',
+                [true, false] => '|',
+                [false, true] => '_',
+                [false, false] => ' ',
+            }).collect())
+            .collect()
+    }
+
+    /// Set the bars from rows of strings, see [`Board::bar_rows`]
+    pub fn set_bar_rows(&mut self, rows: &[String]) {
+        for (j, row) in rows.iter().take(self.height).enumerate() {
+            for (i, c) in row.chars().take(self.width).enumerate() {
+                let idx = self.idx(i, j);
+                self.bars[idx] = [c == '|' || c == '+', c == '_' || c == '+'];
+            }
+        }
+    }
+
+    /// Check if some open cell has no open neighbours, so it does not belong to any word
+    pub fn has_isolated_cells(&self) -> bool {
+        for j in 0..self.height {
+            for i in 0..self.width {
+                if !self.valid(i, j) {
+                    continue;
+                }
+                let hor = (self.valid(i.wrapping_sub(1), j) && !self.has_bar(i.wrapping_sub(1), j, Dir::HOR))
+                    || (self.valid(i + 1, j) && !self.has_bar(i, j, Dir::HOR));
+                let ver = (self.valid(i, j.wrapping_sub(1)) && !self.has_bar(i, j.wrapping_sub(1), Dir::VER))
+                    || (self.valid(i, j + 1) && !self.has_bar(i, j, Dir::VER));
+                if !hor && !ver {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Check if the black and clue cells of the board follow the given symmetry
+    pub fn is_symmetric(&self, symmetry: Symmetry) -> bool {
+        (0..self.height).all(|j| (0..self.width).all(|i| {
+            let black = is_block(self.arr[self.idx(i, j)]);
+            symmetry.partners(i, j, self.width, self.height).iter()
+                .all(|&(x, y)| is_block(self.arr[self.idx(x, y)]) == black)
+        }))
     }
 
-    // Get the elements (x, y, dir, len) representing words to fill in the board
+    /// Get the elements (x, y, dir, len) representing words to fill in the board
     pub fn get_words_pos(&self) -> Vec<WordPos> {
         let mut elems: Vec<WordPos> = Vec::new();
 
@@ -115,6 +461,14 @@ impl Board {
             for i in 0..self.width {
                 if self.valid(i, j) {
                     len += 1;
+                    // a bar closes the word
+                    if self.has_bar(i, j, Dir::HOR) {
+                        if len > 1 {
+                            elems.push(WordPos::new(xstart, ystart, Dir::HOR, len));
+                        }
+                        len = 0;
+                        xstart = i+1;
+                    }
                 }
                 else {
                     if len > 1 {
@@ -139,6 +493,14 @@ impl Board {
             for j in 0..self.height {
                 if self.valid(i, j) {
                     len += 1;
+                    // a bar closes the word
+                    if self.has_bar(i, j, Dir::VER) {
+                        if len > 1 {
+                            elems.push(WordPos::new(xstart, ystart, Dir::VER, len));
+                        }
+                        len = 0;
+                        ystart = j+1;
+                    }
                 }
                 else {
                     if len > 1 {
@@ -157,33 +519,92 @@ impl Board {
         elems
     }
 
-    // Print board
-    pub fn print(&self) {
+    /// Get the word in the given direction covering the cell (x, y)
+    pub fn word_pos_at(&self, x: usize, y: usize, dir: Dir) -> Option<WordPos> {
+        self.get_words_pos().into_iter().find(|wp| wp.dir == dir && wp.index_of(x, y).is_some())
+    }
+
+    /// Get the words positions with their clue number, sorted by number with HOR before VER.
+    /// Numbers are given in reading order to the cells where at least one word starts
+    pub fn get_numbered_words_pos(&self) -> Vec<(usize, WordPos)> {
+        let mut words_pos = self.get_words_pos();
+        words_pos.sort_by_key(|wp| (wp.y, wp.x, wp.dir == Dir::VER));
+
+        let mut numbered: Vec<(usize, WordPos)> = Vec::with_capacity(words_pos.len());
+        let mut number = 0;
+        for word_pos in words_pos {
+            match numbered.last() {
+                Some((_, last)) if last.x == word_pos.x && last.y == word_pos.y => (),
+                _ => number += 1,
+            }
+            numbered.push((number, word_pos));
+        }
+        numbered
+    }
+
+    /// Identifier of a word: its clue number followed by A (across) or D (down), e.g. "12A"
+    pub fn slot_id(&self, word_pos: &WordPos) -> Option<String> {
+        self.get_numbered_words_pos().into_iter()
+            .find(|(_, wp)| wp == word_pos)
+            .map(|(number, wp)| format!("{}{}", number, if wp.dir == Dir::HOR {'A'} else {'D'}))
+    }
+
+    /// Find a word by its identifier, see [`Board::slot_id`]
+    pub fn find_slot(&self, id: &str) -> Option<WordPos> {
+        let id = id.trim().to_uppercase();
+        let dir = match id.chars().last()? {
+            'A' => Dir::HOR,
+            'D' => Dir::VER,
+            _ => return None,
+        };
+        let number: usize = id[..id.len() - 1].parse().ok()?;
+        self.get_numbered_words_pos().into_iter()
+            .find(|(n, wp)| *n == number && wp.dir == dir)
+            .map(|(_, wp)| wp)
+    }
+
+    /// Get the board drawn with borders around each cell, the cells outside the shape of the board
+    /// are left blank without borders
+    pub fn pretty(&self) -> String {
         let mut pretty_matrix = String::new();
+        let inside = |x: usize, y: usize| !self.is_outside(x, y);
+
+        // horizontal line from the segments under each cell, corners join the segments
+        let line = |segments: Vec<char>| -> String {
+            let mut line = String::new();
+            line.push(if segments.first().is_some_and(|&s| s != ' ') {'-'} else {' '});
+            for (i, &segment) in segments.iter().enumerate() {
+                line.push(segment);
+                let next = segments.get(i + 1).copied().unwrap_or(' ');
+                line.push(if segment != ' ' || next != ' ' {'-'} else {' '});
+            }
+            line.push('\n');
+            line
+        };
 
         // upper row
-        for _ in 0..self.width + self.width - 1 + 2 {
-            pretty_matrix.push('-');
-        }
-        pretty_matrix.push('\n');
+        pretty_matrix.push_str(&line((0..self.width).map(|i| if inside(i, 0) {'-'} else {' '}).collect()));
 
-        // actual grid
+        // actual grid, bars are drawn as '‖' and '='
         for j in 0..self.height {
-            pretty_matrix.push('|');
+            pretty_matrix.push(if inside(0, j) {'|'} else {' '});
             for i in 0..self.width {
-                pretty_matrix.push(self.get(i, j));
-                pretty_matrix.push('|');
+                pretty_matrix.push(if inside(i, j) {self.arr[self.idx(i, j)]} else {' '});
+                let separator = if self.has_bar(i, j, Dir::HOR) && i + 1 < self.width {'‖'}
+                    else if inside(i, j) || inside(i + 1, j) {'|'}
+                    else {' '};
+                pretty_matrix.push(separator);
             }
             pretty_matrix.push('\n');
 
-            for _ in 0..self.width + self.width - 1 + 2 {
-                pretty_matrix.push('-');
-            }
-            pretty_matrix.push('\n');
+            pretty_matrix.push_str(&line((0..self.width).map(|i| {
+                if !inside(i, j) && !inside(i, j + 1) {' '}
+                else if self.has_bar(i, j, Dir::VER) && j + 1 < self.height {'='}
+                else {'-'}
+            }).collect()));
         }
 
-        // print
-        println!("{}", pretty_matrix);
+        pretty_matrix
     }
 
 }